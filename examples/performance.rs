@@ -31,6 +31,7 @@ fn main() {
     {
         // With format
         let start = Instant::now();
+        #[allow(clippy::format_in_format_args)]
         for s in &structs {
             log::info!("{}", format!("{}", s));
         }
@@ -0,0 +1,265 @@
+use crate::sink::RecordData;
+use log::Level;
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// `Overflow` is used to choose what happens to a record when the queue is full.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Overflow {
+    /// wait on the logging thread until the writer makes room
+    Block,
+    /// discard the record being logged
+    DropNewest,
+    /// discard the oldest queued record to make room
+    DropOldest,
+    /// discard the record if it is less severe than the level, and wait otherwise
+    DropBelow(Level),
+}
+
+/// `Queue` configures asynchronous logging.
+///
+/// Records are still filtered and formatted on the logging thread, then handed to
+/// a dedicated writer thread through a queue holding at most `capacity` records,
+/// so slow terminals or disks never stall the caller. When records have to be
+/// dropped, the writer thread periodically logs how many were lost as a `Warn`
+/// record with the `loge` target, which every output gets whatever its filter,
+/// and [`dropped_records`](fn.dropped_records.html) tells how many were lost so
+/// far.
+///
+/// ## Example
+///
+/// ```
+/// # extern crate loge;
+/// let queue = loge::Queue::new(4096).overflow(loge::Overflow::DropBelow(log::Level::Warn));
+/// loge::Builder::new().asynchronous(queue).init();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Queue {
+    capacity: usize,
+    overflow: Overflow,
+    report_interval: Duration,
+}
+
+impl Queue {
+    /// Create a queue of `capacity` records that blocks when full.
+    pub fn new(capacity: usize) -> Queue {
+        Queue {
+            capacity: capacity.max(1),
+            overflow: Overflow::Block,
+            report_interval: Duration::from_secs(5),
+        }
+    }
+
+    /// Set what happens to records logged while the queue is full.
+    pub fn overflow(mut self, overflow: Overflow) -> Queue {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Set how often the number of dropped records is reported, 5 seconds by default.
+    pub fn report_interval(mut self, interval: Duration) -> Queue {
+        self.report_interval = interval;
+        self
+    }
+}
+
+/// A formatted record waiting for the writer thread, as lines for the outputs at
+/// the given indexes, and a copy of the record if any of them wants it.
+pub struct Entry {
+    pub level: Level,
+    pub lines: Vec<(usize, Arc<str>)>,
+    pub record: Option<RecordData>,
+}
+
+thread_local! {
+    static WRITER_THREAD: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Whether the current thread is a writer thread, which must never queue records.
+pub fn on_writer_thread() -> bool {
+    WRITER_THREAD.with(Cell::get)
+}
+
+struct State {
+    entries: VecDeque<Entry>,
+    // The writer thread is writing entries it took from the queue.
+    busy: bool,
+    stopped: bool,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    not_empty: Condvar,
+    // Notified whenever the writer thread finished a batch.
+    written: Condvar,
+    // Every dropped record since the writer started.
+    dropped: AtomicU64,
+    capacity: usize,
+    overflow: Overflow,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    // Wait until every queued entry is written, or `timeout` elapsed.
+    fn wait_written(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.lock();
+        while !state.entries.is_empty() || state.busy {
+            let now = Instant::now();
+            if now >= deadline {
+                return false;
+            }
+            state = self
+                .written
+                .wait_timeout(state, deadline - now)
+                .map(|(state, _)| state)
+                .unwrap_or_else(|e| e.into_inner().0);
+        }
+        true
+    }
+}
+
+pub struct AsyncWriter {
+    shared: Arc<Shared>,
+    handle: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl AsyncWriter {
+    /// Spawn the writer thread, which hands every entry to `sink`.
+    pub fn new<F>(config: Queue, sink: F) -> AsyncWriter
+    where
+        F: Fn(Entry) + Send + 'static,
+    {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                entries: VecDeque::with_capacity(config.capacity),
+                busy: false,
+                stopped: false,
+            }),
+            not_empty: Condvar::new(),
+            written: Condvar::new(),
+            dropped: AtomicU64::new(0),
+            capacity: config.capacity,
+            overflow: config.overflow,
+        });
+        let worker = shared.clone();
+        let handle = thread::Builder::new().name("loge-writer".to_owned()).spawn(move || {
+            WRITER_THREAD.with(|flag| flag.set(true));
+            run(&worker, config.report_interval, sink)
+        });
+        if handle.is_err() {
+            shared.lock().stopped = true;
+        }
+        AsyncWriter { shared, handle: Mutex::new(handle.ok()) }
+    }
+
+    /// Queue `entry`, applying the overflow policy if the queue is full.
+    ///
+    /// The entry is given back if the writer thread is not running, so that the
+    /// caller can write it itself.
+    pub fn push(&self, entry: Entry) -> Option<Entry> {
+        let shared = &self.shared;
+        let mut state = shared.lock();
+        while state.entries.len() >= shared.capacity && !state.stopped {
+            let block = match shared.overflow {
+                Overflow::Block => true,
+                Overflow::DropBelow(level) => entry.level <= level,
+                Overflow::DropNewest => false,
+                Overflow::DropOldest => {
+                    state.entries.pop_front();
+                    shared.dropped.fetch_add(1, Ordering::Relaxed);
+                    continue;
+                }
+            };
+            if !block {
+                shared.dropped.fetch_add(1, Ordering::Relaxed);
+                return None;
+            }
+            state = shared.written.wait(state).unwrap_or_else(|e| e.into_inner());
+        }
+        if state.stopped {
+            return Some(entry);
+        }
+        state.entries.push_back(entry);
+        shared.not_empty.notify_one();
+        None
+    }
+
+    /// The number of records dropped so far.
+    pub fn dropped(&self) -> u64 {
+        self.shared.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Wait until the queued entries are written, for at most `timeout`.
+    pub fn flush(&self, timeout: Duration) -> bool {
+        self.shared.wait_written(timeout)
+    }
+
+    /// Write the queued entries and stop the writer thread, waiting at most
+    /// `timeout`. Entries pushed afterwards are given back to the caller.
+    pub fn stop(&self, timeout: Duration) -> bool {
+        self.shared.lock().stopped = true;
+        self.shared.not_empty.notify_all();
+        if !self.shared.wait_written(timeout) {
+            return false;
+        }
+        let handle = self.handle.lock().unwrap_or_else(|e| e.into_inner()).take();
+        if let Some(handle) = handle {
+            let _ = handle.join();
+        }
+        true
+    }
+}
+
+fn run<F: Fn(Entry)>(shared: &Shared, report_interval: Duration, sink: F) {
+    let mut last_report = Instant::now();
+    let mut reported = 0;
+    loop {
+        let (batch, stopped): (Vec<Entry>, bool) = {
+            let mut state = shared.lock();
+            if state.entries.is_empty() && !state.stopped {
+                state = shared
+                    .not_empty
+                    .wait_timeout(state, report_interval)
+                    .map(|(state, _)| state)
+                    .unwrap_or_else(|e| e.into_inner().0);
+            }
+            state.busy = true;
+            shared.written.notify_all();
+            (state.entries.drain(..).collect(), state.stopped)
+        };
+        batch.into_iter().for_each(&sink);
+
+        if stopped || last_report.elapsed() >= report_interval {
+            last_report = Instant::now();
+            let total = shared.dropped.load(Ordering::Relaxed);
+            if total > reported {
+                crate::log_unfiltered(
+                    &log::Record::builder()
+                        .level(Level::Warn)
+                        .target("loge")
+                        .args(format_args!(
+                            "{} log records were dropped because the queue was full",
+                            total - reported
+                        ))
+                        .build(),
+                );
+                reported = total;
+            }
+        }
+
+        let mut state = shared.lock();
+        state.busy = false;
+        shared.written.notify_all();
+        if stopped && state.entries.is_empty() {
+            return;
+        }
+    }
+}
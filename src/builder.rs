@@ -0,0 +1,422 @@
+use crate::asyncwriter::Queue;
+use crate::color::ColorChoice;
+#[cfg(feature = "config")]
+use crate::config::{self, Config, Watcher};
+use crate::error::{Destination, OnError};
+use crate::filewriter::Rotation;
+use crate::filter::Filter;
+use crate::guard::LogeGuard;
+use crate::handle::LogeHandle;
+use crate::output::{Channel, Output, Stream};
+use crate::sink::{FileSink, NoSink};
+use crate::theme::Theme;
+use crate::{parse_formats, LogeFormat, LogeLogger, Settings, Sinks, LOGGER};
+use log::{Level, LevelFilter, SetLoggerError};
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+#[cfg(feature = "config")]
+use std::time::Duration;
+
+/// `Builder` is used to configure and install the global logger.
+///
+/// Every setting has a sensible default, so only the options you care about need
+/// to be called before finishing with [`init`](#method.init) or
+/// [`try_init`](#method.try_init). When an environment variable name is given for
+/// the level or the format, a value found in the environment takes precedence over
+/// the one set in code.
+///
+/// Level filters follow the `env_logger` syntax, for example
+/// `RUST_LOG=info,my_crate=debug,hyper=off`, so the verbosity can be tuned per module.
+///
+/// ## Example
+///
+/// ```
+/// # extern crate loge;
+/// # extern crate log;
+/// loge::Builder::new()
+///     .level(log::Level::Info)
+///     .format(loge::LogeFormat::Fileline)
+///     .level_env("RUST_LOG")
+///     .init();
+///
+/// log::info!("configured by the builder");
+/// ```
+pub struct Builder {
+    filter: Filter,
+    format: LogeFormat,
+    file: Option<PathBuf>,
+    rotation: Rotation,
+    reopen_when_moved: bool,
+    console: bool,
+    stream: Stream,
+    color: ColorChoice,
+    theme: Theme,
+    outputs: Vec<Output>,
+    queue: Option<Queue>,
+    on_error: OnError,
+    level_env: Option<String>,
+    format_env: Option<String>,
+    stream_env: Option<String>,
+    theme_env: Option<String>,
+    #[cfg(feature = "config")]
+    config_file: Option<PathBuf>,
+    #[cfg(feature = "config")]
+    config_interval: Option<Duration>,
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Builder::new()
+    }
+}
+
+impl Builder {
+    /// Create a builder logging `Level::Error` and above in the `Target` format,
+    /// to the terminal only, without reading any environment variable.
+    pub fn new() -> Builder {
+        Builder {
+            filter: Filter::new(LevelFilter::Error),
+            format: LogeFormat::Target,
+            file: None,
+            rotation: Rotation::new(),
+            reopen_when_moved: false,
+            console: true,
+            stream: Stream::Stdout,
+            color: ColorChoice::Auto,
+            theme: Theme::default(),
+            outputs: Vec::new(),
+            queue: None,
+            on_error: OnError::default(),
+            level_env: None,
+            format_env: None,
+            stream_env: None,
+            theme_env: None,
+            #[cfg(feature = "config")]
+            config_file: None,
+            #[cfg(feature = "config")]
+            config_interval: None,
+        }
+    }
+
+    /// Create a builder reading the level from `RUST_LOG`, the format from
+    /// `LOGE_FORMAT`, the console stream from `LOGE_TARGET` and the theme from
+    /// `LOGE_THEME`.
+    pub fn from_default_env() -> Builder {
+        Builder::new()
+            .level_env("RUST_LOG")
+            .format_env("LOGE_FORMAT")
+            .stream_env("LOGE_TARGET")
+            .theme_env("LOGE_THEME")
+    }
+
+    /// Set the most verbose level that will be logged by default.
+    pub fn level(mut self, level: Level) -> Builder {
+        self.filter.insert(None, level.to_level_filter());
+        self
+    }
+
+    /// Set the most verbose level logged by `module` and its submodules.
+    pub fn filter_module(mut self, module: &str, level: LevelFilter) -> Builder {
+        self.filter.insert(Some(module), level);
+        self
+    }
+
+    /// Add directives in the `RUST_LOG` syntax, such as `warn,my_crate=debug`.
+    pub fn parse_filters(mut self, spec: &str) -> Builder {
+        self.filter.extend(spec);
+        self
+    }
+
+    /// Set the output format.
+    pub fn format(mut self, format: LogeFormat) -> Builder {
+        self.format = format;
+        self
+    }
+
+    /// Also append every record to `log_file`, without colors.
+    pub fn file<P: AsRef<Path>>(mut self, log_file: P) -> Builder {
+        self.file = Some(log_file.as_ref().to_path_buf());
+        self
+    }
+
+    /// Rotate the file set with [`file`](#method.file) according to `rotation`.
+    pub fn rotation(mut self, rotation: Rotation) -> Builder {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Reopen the file set with [`file`](#method.file) when its path no longer
+    /// points to it, e.g. after logrotate renamed it. The path is checked at most
+    /// once per second. See also [`reopen`](fn.reopen.html).
+    pub fn reopen_when_moved(mut self) -> Builder {
+        self.reopen_when_moved = true;
+        self
+    }
+
+    /// Set whether records are written to the console, `true` by default.
+    pub fn console(mut self, enabled: bool) -> Builder {
+        self.console = enabled;
+        self
+    }
+
+    /// Set which stream the console lines go to, standard output by default.
+    ///
+    /// Colors are turned on separately for each stream which is a terminal, so
+    /// piping standard output into another program leaves its data clean.
+    pub fn stream(mut self, stream: Stream) -> Builder {
+        self.stream = stream;
+        self
+    }
+
+    /// Set whether console lines are colored (needs `colored`). By default, only
+    /// terminals get colors, see [`ColorChoice`](enum.ColorChoice.html).
+    pub fn color(mut self, color: ColorChoice) -> Builder {
+        self.color = color;
+        self
+    }
+
+    /// Set the colors of colored lines, [`Theme::default`](struct.Theme.html) by
+    /// default.
+    pub fn theme(mut self, theme: Theme) -> Builder {
+        self.theme = theme;
+        self
+    }
+
+    /// Also write records to `output`, with its own level, format and colors.
+    pub fn output(mut self, output: Output) -> Builder {
+        self.outputs.push(output);
+        self
+    }
+
+    /// Write records from a background thread fed through `queue`.
+    pub fn asynchronous(mut self, queue: Queue) -> Builder {
+        self.queue = Some(queue);
+        self
+    }
+
+    /// Set what happens when a record cannot be written, reported once to stderr
+    /// by default.
+    pub fn on_error(mut self, on_error: OnError) -> Builder {
+        self.on_error = on_error;
+        self
+    }
+
+    /// Read the level from the environment variable `name`, if it is set.
+    pub fn level_env(mut self, name: &str) -> Builder {
+        self.level_env = Some(name.to_owned());
+        self
+    }
+
+    /// Read the format from the environment variable `name`, if it is set.
+    pub fn format_env(mut self, name: &str) -> Builder {
+        self.format_env = Some(name.to_owned());
+        self
+    }
+
+    /// Read the console stream from the environment variable `name`, if it is set
+    /// to `stdout`, `stderr` or `split`.
+    pub fn stream_env(mut self, name: &str) -> Builder {
+        self.stream_env = Some(name.to_owned());
+        self
+    }
+
+    /// Read the theme from the environment variable `name`, if it is set to the
+    /// name of a preset, such as `solarized`.
+    pub fn theme_env(mut self, name: &str) -> Builder {
+        self.theme_env = Some(name.to_owned());
+        self
+    }
+
+    /// Load the level, format, console and file settings from the configuration file
+    /// at `path` when initializing (needs the `config` feature).
+    ///
+    /// Files ending in `.toml` are read as TOML, others as JSON, for example:
+    ///
+    /// ```toml
+    /// level = "info,my_crate::db=debug"
+    /// format = "json"
+    /// console = true
+    /// stream = "split" # as in `LOGE_TARGET`
+    ///
+    /// [file]
+    /// path = "app.log"
+    /// max_bytes = 67108864
+    /// period = "daily"
+    /// naming = "dated"
+    /// keep = 14
+    /// max_age = 604800 # seconds
+    /// compress = "gzip"
+    /// reopen_when_moved = true
+    /// ```
+    ///
+    /// `file` may also be just a path. The settings of the file replace the ones set
+    /// in code, `level` adding its directives over those of code, and environment
+    /// variables given to the builder still take precedence, also after a reload.
+    /// An invalid file is reported to stderr and ignored. Other outputs, such as
+    /// syslog or custom sinks, are set in code with [`output`](#method.output).
+    #[cfg(feature = "config")]
+    pub fn config_file<P: AsRef<Path>>(mut self, path: P) -> Builder {
+        self.config_file = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Check the configuration file every `interval`, and apply the settings that
+    /// changed (needs the `config` feature).
+    ///
+    /// Settings removed from the file keep their current value, and `console` and
+    /// `stream` take effect at the next start. When the file is invalid, the
+    /// previous configuration is kept and a `Warn` record with the `loge` target,
+    /// written whatever the level, explains why.
+    #[cfg(feature = "config")]
+    pub fn watch_config(mut self, interval: Duration) -> Builder {
+        self.config_interval = Some(interval);
+        self
+    }
+
+    /// Initializes the global logger with the built configuration, returning a
+    /// handle to change its level and format at runtime.
+    ///
+    /// # Panics
+    ///
+    /// This function fails to set the global logger if one has already been set.
+    pub fn init(self) -> LogeHandle {
+        self.try_init().unwrap()
+    }
+
+    /// Initializes the global logger with the built configuration, returning a
+    /// handle to change its level and format at runtime.
+    ///
+    /// # Errors
+    ///
+    /// This function fails to set the global logger if one has already been set.
+    pub fn try_init(self) -> Result<LogeHandle, SetLoggerError> {
+        #[cfg(all(windows, feature = "colored"))]
+        crate::set_up_color_terminal();
+
+        #[cfg(feature = "config")]
+        let config = self.config_file.as_deref().map(load_config).unwrap_or_default();
+
+        // The file's level goes over the directives set in code, and the environment
+        // over both, here and whenever the watcher reloads the file.
+        let level_env = self.level_env.and_then(|name| env::var(name).ok());
+        let mut filter = self.filter.clone();
+        let mut format = self.format;
+        let (file, rotation, reopen_when_moved) =
+            (self.file, self.rotation, self.reopen_when_moved);
+        #[cfg(feature = "config")]
+        if let Some(ref level) = config.level {
+            filter.extend(level);
+        }
+        #[cfg(feature = "config")]
+        if let Some(ref name) = config.format {
+            format = parse_formats(name);
+        }
+        #[cfg(feature = "config")]
+        let (file, rotation, reopen_when_moved) = match config.file {
+            Some(ref file) => (
+                Some(file.path.clone()),
+                file.rotation().unwrap_or_default(),
+                reopen_when_moved || file.reopen_when_moved,
+            ),
+            None => (file, rotation, reopen_when_moved),
+        };
+        if let Some(ref spec) = level_env {
+            filter.extend(spec);
+        }
+        if let Some(Ok(s)) = self.format_env.map(env::var) {
+            format = parse_formats(&s);
+        }
+        #[cfg(feature = "config")]
+        let (mut stream, console) =
+            (config.stream.unwrap_or(self.stream), config.console.unwrap_or(self.console));
+        #[cfg(not(feature = "config"))]
+        let (mut stream, console) = (self.stream, self.console);
+        if let Some(Ok(s)) = self.stream_env.map(env::var) {
+            match s.parse() {
+                Ok(parsed) => stream = parsed,
+                Err(e) => eprintln!("warning: {}, ignoring it", e),
+            }
+        }
+        let mut theme = self.theme;
+        if let Some(Ok(s)) = self.theme_env.map(env::var) {
+            match s.parse() {
+                Ok(parsed) => theme = parsed,
+                Err(e) => eprintln!("warning: {}, ignoring it", e),
+            }
+        }
+
+        // The file comes first and is always present, so that a reloaded
+        // configuration can add one; until then, records skip it.
+        let file = match file {
+            Some(path) if reopen_when_moved => Channel::new(
+                Box::new(FileSink::new(path, rotation).reopen_when_moved()),
+                Destination::File,
+                false,
+            ),
+            Some(path) => {
+                Channel::new(Box::new(FileSink::new(path, rotation)), Destination::File, false)
+            }
+            None => Channel::inactive(Box::new(NoSink), Destination::File),
+        };
+        let mut channels = vec![file];
+        if console {
+            channels.extend(stream.install(self.color));
+        }
+        channels.extend(self.outputs.into_iter().map(Output::install));
+
+        let outputs_level = channels
+            .iter()
+            .filter_map(|channel| channel.filter.as_ref().map(Filter::max_level))
+            .max()
+            .unwrap_or(LevelFilter::Off);
+        let settings = Settings { filter, format, theme, outputs_level };
+        let max_level = settings.max_level();
+        let sinks = Arc::new(Sinks::new(channels, self.queue, self.on_error));
+        let settings = Arc::new(RwLock::new(settings));
+        let logger = LogeLogger::new(settings.clone(), sinks.clone());
+        log::set_boxed_logger(Box::new(logger.clone()))?;
+        log::set_max_level(max_level);
+        let handle = LogeHandle::new(settings);
+        #[cfg(feature = "config")]
+        {
+            if let (Some(path), Some(interval)) = (self.config_file, self.config_interval) {
+                let (filter, sinks) = (self.filter, sinks.clone());
+                Watcher::new(path, handle.clone(), sinks, config, filter, level_env)
+                    .spawn(interval);
+            }
+        }
+        let _ = LOGGER.set(logger);
+        Ok(handle)
+    }
+
+    /// Initializes the global logger, returning a guard that flushes every sink
+    /// and stops the writer thread when dropped.
+    ///
+    /// # Panics
+    ///
+    /// This function fails to set the global logger if one has already been set.
+    pub fn init_with_guard(self) -> LogeGuard {
+        self.try_init_with_guard().unwrap()
+    }
+
+    /// Initializes the global logger, returning a guard that flushes every sink
+    /// and stops the writer thread when dropped.
+    ///
+    /// # Errors
+    ///
+    /// This function fails to set the global logger if one has already been set.
+    pub fn try_init_with_guard(self) -> Result<LogeGuard, SetLoggerError> {
+        let handle = self.try_init()?;
+        Ok(LogeGuard::new(handle))
+    }
+}
+
+// Load the configuration file at `path`, reporting errors to stderr.
+#[cfg(feature = "config")]
+fn load_config(path: &Path) -> Config {
+    config::load(path).unwrap_or_else(|e| {
+        eprintln!("warning: failed to load {}: {}, ignoring it", path.display(), e);
+        Config::default()
+    })
+}
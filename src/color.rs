@@ -0,0 +1,83 @@
+use std::env;
+use std::fmt;
+
+/// `ColorChoice` is used to choose whether a destination gets colored lines
+/// (needs `colored`).
+///
+/// `Auto` colors terminals only, and follows the conventions of
+/// [no-color.org](https://no-color.org) and
+/// [bixense.com/clicolors](https://bixense.com/clicolors): `CLICOLOR_FORCE` set
+/// to anything but `0` turns colors on, otherwise a non-empty `NO_COLOR`,
+/// `CLICOLOR=0` or `TERM=dumb` turn them off. The choice is made once per
+/// destination when the logger is installed, and files or custom sinks never get
+/// colors unless asked for with `Always`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum ColorChoice {
+    /// color terminals, unless the environment says otherwise
+    #[default]
+    Auto,
+    /// always color, e.g. for a pager which understands ANSI escapes
+    Always,
+    /// never color
+    Never,
+}
+
+impl ColorChoice {
+    /// Whether a destination gets colors, given whether it is a terminal, or
+    /// `None` if it is not a standard stream.
+    pub(crate) fn enabled(self, terminal: Option<bool>) -> bool {
+        cfg!(feature = "colored")
+            && match self {
+                ColorChoice::Auto => {
+                    terminal.is_some_and(|terminal| from_env().unwrap_or(terminal))
+                }
+                ColorChoice::Always => true,
+                ColorChoice::Never => false,
+            }
+    }
+}
+
+// What the environment says about colors, if anything.
+fn from_env() -> Option<bool> {
+    let var = |name| env::var(name).ok().filter(|value| !value.is_empty());
+    if var("CLICOLOR_FORCE").is_some_and(|value| value != "0") {
+        Some(true)
+    } else if var("NO_COLOR").is_some()
+        || var("CLICOLOR").is_some_and(|value| value == "0")
+        || var("TERM").is_some_and(|value| value == "dumb")
+    {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// `Painted` displays a value wrapped in the ANSI escapes of the SGR parameters
+/// `sgr`, such as `1;31` for bold red, keeping the width and alignment given to
+/// the value.
+pub(crate) struct Painted<'a, T> {
+    sgr: &'a str,
+    value: T,
+}
+
+impl<'a, T: fmt::Display> Painted<'a, T> {
+    pub fn new(sgr: &'a str, value: T) -> Painted<'a, T> {
+        Painted { sgr, value }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Painted<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.sgr.is_empty() {
+            return fmt::Display::fmt(&self.value, f);
+        }
+        write!(f, "\x1b[{}m", self.sgr)?;
+        fmt::Display::fmt(&self.value, f)?;
+        f.write_str("\x1b[0m")
+    }
+}
+
+pub(crate) const BOLD: &str = "1";
+pub(crate) const DIMMED: &str = "2";
+pub(crate) const ITALIC: &str = "3";
+pub(crate) const UNDERLINE: &str = "4";
@@ -0,0 +1,150 @@
+use crate::filewriter::Naming;
+use std::ffi::OsStr;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+/// `Compression` is used to choose how rotated log segments are compressed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Compression {
+    /// gzip, adding a `.gz` extension (needs the `gzip` feature)
+    #[cfg(feature = "gzip")]
+    Gzip,
+    /// zstd, adding a `.zst` extension (needs the `zstd` feature)
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+impl Compression {
+    fn extension(self) -> &'static str {
+        match self {
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => "gz",
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => "zst",
+        }
+    }
+
+    fn encode(self, mut source: File, target: File) -> io::Result<File> {
+        match self {
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => {
+                let mut encoder = flate2::write::GzEncoder::new(target, Default::default());
+                io::copy(&mut source, &mut encoder)?;
+                encoder.finish()
+            }
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => {
+                let mut encoder = zstd::stream::write::Encoder::new(target, 0)?;
+                io::copy(&mut source, &mut encoder)?;
+                encoder.finish()
+            }
+        }
+    }
+}
+
+// A closed segment to compress, and the rotation count right after it was closed.
+struct Job {
+    source: PathBuf,
+    generation: u64,
+}
+
+/// Compresses closed segments on a background thread.
+///
+/// Numbered segments keep being renamed by later rotations while they are
+/// compressed, so the thread works on a hidden temporary file and only swaps it
+/// in, under the rotation lock, once it is complete.
+#[derive(Debug)]
+pub struct Compressor {
+    sender: Option<Sender<Job>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Compressor {
+    pub fn new(
+        active: PathBuf,
+        naming: Naming,
+        compression: Compression,
+        generation: Arc<Mutex<u64>>,
+    ) -> Compressor {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let handle = thread::Builder::new()
+            .name("loge-compress".to_owned())
+            .spawn(move || {
+                for job in receiver {
+                    if let Err(e) = compress(&active, naming, compression, &generation, &job) {
+                        eprintln!("warning: failed to compress {}: {}", job.source.display(), e);
+                    }
+                }
+            })
+            .ok();
+        Compressor { sender: Some(sender), handle }
+    }
+
+    /// Queue `source`, closed by the rotation numbered `generation`.
+    pub fn compress(&self, source: PathBuf, generation: u64) {
+        if let Some(ref sender) = self.sender {
+            let _ = sender.send(Job { source, generation });
+        }
+    }
+}
+
+impl Drop for Compressor {
+    fn drop(&mut self) {
+        self.sender.take();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn compress(
+    active: &Path,
+    naming: Naming,
+    compression: Compression,
+    generation: &Mutex<u64>,
+    job: &Job,
+) -> io::Result<()> {
+    // Where the segment is now, given how many rotations happened since it was closed.
+    let locate = |generation: u64| match naming {
+        Naming::Numbered => {
+            let prefix = active.file_name().and_then(OsStr::to_str).unwrap_or_default();
+            let index = 1 + generation - job.generation;
+            job.source.with_file_name(format!("{}.{}", prefix, index))
+        }
+        #[cfg(feature = "chrono")]
+        Naming::Dated => job.source.clone(),
+    };
+
+    let current = locate(*generation.lock().unwrap_or_else(|e| e.into_inner()));
+    let source = match File::open(&current) {
+        Ok(source) => source,
+        // Already deleted by the retention policy.
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    let modified = source.metadata()?.modified()?;
+    let name = job.source.file_name().and_then(OsStr::to_str).unwrap_or_default();
+    let temporary = job.source.with_file_name(format!(".{}.{}.tmp", name, compression.extension()));
+    let target = compression.encode(source, File::create(&temporary)?)?;
+    target.sync_all()?;
+    // Keep the age of the segment for the retention policy.
+    target.set_modified(modified)?;
+    drop(target);
+
+    // Hold the rotation lock so the segment is not renamed while it is swapped.
+    let generation = generation.lock().unwrap_or_else(|e| e.into_inner());
+    let current = locate(*generation);
+    if !current.exists() {
+        return fs::remove_file(&temporary);
+    }
+    let name = current.file_name().and_then(OsStr::to_str).unwrap_or_default();
+    fs::rename(
+        &temporary,
+        current.with_file_name(format!("{}.{}", name, compression.extension())),
+    )?;
+    fs::remove_file(&current)
+}
@@ -0,0 +1,295 @@
+//! Settings loaded from a TOML or JSON file, and the thread re-applying them when
+//! the file changes.
+
+#[cfg(feature = "chrono")]
+use crate::filewriter::Period;
+use crate::filewriter::{Naming, Rotation};
+use crate::filter::Filter;
+use crate::handle::LogeHandle;
+use crate::output::Stream;
+use crate::sink::FileSink;
+use crate::{try_parse_format, Sinks};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// The settings found in a configuration file; missing ones are `None`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Config {
+    /// Level directives in the `RUST_LOG` syntax.
+    pub level: Option<String>,
+    /// A format name, as in `LOGE_FORMAT`.
+    pub format: Option<String>,
+    /// Whether records are written to the console, read at init only.
+    pub console: Option<bool>,
+    /// A console stream, as in `LOGE_TARGET`, read at init only.
+    pub stream: Option<Stream>,
+    pub file: Option<FileConfig>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FileConfig {
+    pub path: PathBuf,
+    max_bytes: Option<u64>,
+    period: Option<String>,
+    naming: Option<String>,
+    keep: Option<u64>,
+    max_age: Option<u64>,
+    compress: Option<String>,
+    pub reopen_when_moved: bool,
+}
+
+impl FileConfig {
+    pub fn rotation(&self) -> Result<Rotation, String> {
+        let mut rotation = Rotation::new();
+        if let Some(bytes) = self.max_bytes {
+            rotation = rotation.max_bytes(bytes);
+        }
+        if let Some(ref period) = self.period {
+            #[cfg(feature = "chrono")]
+            {
+                rotation = rotation.period(match period.as_str() {
+                    "hourly" => Period::Hourly,
+                    "daily" => Period::Daily,
+                    _ => return Err(format!("unknown period `{}`", period)),
+                });
+            }
+            #[cfg(not(feature = "chrono"))]
+            return Err(format!("period `{}` requires the `chrono` feature", period));
+        }
+        if let Some(ref naming) = self.naming {
+            rotation = rotation.naming(match naming.as_str() {
+                "numbered" => Naming::Numbered,
+                #[cfg(feature = "chrono")]
+                "dated" => Naming::Dated,
+                _ => return Err(format!("unknown naming `{}`", naming)),
+            });
+        }
+        if let Some(count) = self.keep {
+            rotation = rotation.keep(count as usize);
+        }
+        if let Some(seconds) = self.max_age {
+            rotation = rotation.max_age(Duration::from_secs(seconds));
+        }
+        if let Some(ref compress) = self.compress {
+            #[cfg(any(feature = "gzip", feature = "zstd"))]
+            {
+                use crate::compressor::Compression;
+                rotation = rotation.compress(match compress.as_str() {
+                    #[cfg(feature = "gzip")]
+                    "gzip" => Compression::Gzip,
+                    #[cfg(feature = "zstd")]
+                    "zstd" => Compression::Zstd,
+                    _ => return Err(format!("unsupported compression `{}`", compress)),
+                });
+            }
+            #[cfg(not(any(feature = "gzip", feature = "zstd")))]
+            return Err(format!(
+                "compression `{}` requires the `gzip` or `zstd` feature",
+                compress
+            ));
+        }
+        Ok(rotation)
+    }
+}
+
+/// Read and validate the configuration file at `path`.
+///
+/// Files ending in `.toml` are parsed as TOML, others as JSON.
+pub(crate) fn load(path: &Path) -> Result<Config, String> {
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let value = match path.extension().and_then(|e| e.to_str()) {
+        Some("toml") => toml::from_str(&text).map_err(|e| e.to_string())?,
+        _ => serde_json::from_str(&text).map_err(|e| e.to_string())?,
+    };
+    let config = interpret(value)?;
+    if let Some(ref format) = config.format {
+        try_parse_format(format)?;
+    }
+    if let Some(ref file) = config.file {
+        file.rotation()?;
+    }
+    Ok(config)
+}
+
+fn interpret(value: Value) -> Result<Config, String> {
+    let mut object = match value {
+        Value::Object(object) => object,
+        _ => return Err("expected a table of settings".to_owned()),
+    };
+    let config = Config {
+        level: string(&mut object, "level")?,
+        format: string(&mut object, "format")?,
+        console: boolean(&mut object, "console")?,
+        stream: string(&mut object, "stream")?.map(|s| s.parse()).transpose()?,
+        file: match object.remove("file") {
+            None => None,
+            Some(Value::String(path)) => Some(FileConfig {
+                path: PathBuf::from(path),
+                max_bytes: None,
+                period: None,
+                naming: None,
+                keep: None,
+                max_age: None,
+                compress: None,
+                reopen_when_moved: false,
+            }),
+            Some(Value::Object(mut file)) => {
+                let path = string(&mut file, "path")?.ok_or("missing `file.path`")?;
+                let file_config = FileConfig {
+                    path: PathBuf::from(path),
+                    max_bytes: integer(&mut file, "max_bytes")?,
+                    period: string(&mut file, "period")?,
+                    naming: string(&mut file, "naming")?,
+                    keep: integer(&mut file, "keep")?,
+                    max_age: integer(&mut file, "max_age")?,
+                    compress: string(&mut file, "compress")?,
+                    reopen_when_moved: boolean(&mut file, "reopen_when_moved")?.unwrap_or(false),
+                };
+                unknown(&file, "file.")?;
+                Some(file_config)
+            }
+            Some(_) => return Err("`file` must be a path or a table".to_owned()),
+        },
+    };
+    unknown(&object, "")?;
+    Ok(config)
+}
+
+fn string(object: &mut Map<String, Value>, key: &str) -> Result<Option<String>, String> {
+    match object.remove(key) {
+        None => Ok(None),
+        Some(Value::String(s)) => Ok(Some(s)),
+        Some(_) => Err(format!("`{}` must be a string", key)),
+    }
+}
+
+fn boolean(object: &mut Map<String, Value>, key: &str) -> Result<Option<bool>, String> {
+    match object.remove(key) {
+        None => Ok(None),
+        Some(Value::Bool(b)) => Ok(Some(b)),
+        Some(_) => Err(format!("`{}` must be a boolean", key)),
+    }
+}
+
+fn integer(object: &mut Map<String, Value>, key: &str) -> Result<Option<u64>, String> {
+    match object.remove(key) {
+        None => Ok(None),
+        Some(value) => value
+            .as_u64()
+            .map(Some)
+            .ok_or_else(|| format!("`{}` must be a non-negative integer", key)),
+    }
+}
+
+fn unknown(object: &Map<String, Value>, prefix: &str) -> Result<(), String> {
+    match object.keys().next() {
+        Some(key) => Err(format!("unknown setting `{}{}`", prefix, key)),
+        None => Ok(()),
+    }
+}
+
+// What identifies a version of the file, `None` while it cannot be read.
+fn stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Polls the configuration file and applies the settings that changed.
+pub(crate) struct Watcher {
+    path: PathBuf,
+    handle: LogeHandle,
+    sinks: Arc<Sinks>,
+    current: Config,
+    // The directives set in code, and the spec read from the level variable.
+    filter: Filter,
+    level_env: Option<String>,
+    stamp: Option<(SystemTime, u64)>,
+}
+
+impl Watcher {
+    /// Watch `path`, whose settings `current` were applied at init over the
+    /// directives set in code, under the spec read from the level variable.
+    pub fn new(
+        path: PathBuf,
+        handle: LogeHandle,
+        sinks: Arc<Sinks>,
+        current: Config,
+        filter: Filter,
+        level_env: Option<String>,
+    ) -> Watcher {
+        let stamp = stamp(&path);
+        Watcher { path, handle, sinks, current, filter, level_env, stamp }
+    }
+
+    /// Check the file every `interval` on a background thread.
+    pub fn spawn(mut self, interval: Duration) {
+        let path = self.path.clone();
+        let spawned = thread::Builder::new().name("loge-config".to_owned()).spawn(move || loop {
+            thread::sleep(interval);
+            self.poll();
+        });
+        if let Err(e) = spawned {
+            eprintln!("warning: failed to watch {}: {}", path.display(), e);
+        }
+    }
+
+    fn poll(&mut self) {
+        let stamp = stamp(&self.path);
+        if stamp.is_none() || stamp == self.stamp {
+            return;
+        }
+        self.stamp = stamp;
+        match load(&self.path) {
+            Ok(config) => self.apply(config),
+            // Logged past the filter, which may well hide warnings.
+            Err(e) => crate::log_unfiltered(
+                &log::Record::builder()
+                    .level(log::Level::Warn)
+                    .target("loge")
+                    .args(format_args!(
+                        "invalid configuration in {}: {}; keeping the previous configuration",
+                        self.path.display(),
+                        e
+                    ))
+                    .build(),
+            ),
+        }
+    }
+
+    // Apply the settings that changed; settings removed from the file are kept, and
+    // the console ones take a restart.
+    fn apply(&mut self, config: Config) {
+        if config.level != self.current.level {
+            // Rebuilt as at init, so the environment still takes precedence.
+            if let Some(ref level) = config.level {
+                let mut filter = self.filter.clone();
+                filter.extend(level);
+                if let Some(ref spec) = self.level_env {
+                    filter.extend(spec);
+                }
+                self.handle.set_filter(filter);
+            }
+        }
+        if config.format != self.current.format {
+            if let Some(format) = config.format.as_deref().and_then(|f| try_parse_format(f).ok()) {
+                self.handle.set_format(format);
+            }
+        }
+        if config.file != self.current.file {
+            if let Some(ref file) = config.file {
+                if let Ok(rotation) = file.rotation() {
+                    let mut sink = FileSink::new(&file.path, rotation);
+                    if file.reopen_when_moved {
+                        sink = sink.reopen_when_moved();
+                    }
+                    self.sinks.set_file(Some(sink));
+                }
+            }
+        }
+        self.current = config;
+    }
+}
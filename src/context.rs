@@ -0,0 +1,90 @@
+//! A per-thread diagnostic context, attached to every record logged on the thread.
+//!
+//! Fields pushed on the context are rendered after the message as `key=value` pairs
+//! in the `Target` and `Fileline` formats, as `{context}` in templates and under a
+//! `context` object in JSON. When a key is pushed twice, the innermost value wins.
+//!
+//! ## Example
+//!
+//! ```
+//! # extern crate loge;
+//! # loge::init();
+//! let _request = loge::context::push("request_id", "7f3a");
+//! loge::context::with_context("tenant", "acme", || {
+//!     log::error!("rendered with request_id=7f3a tenant=acme");
+//! });
+//! log::error!("rendered with request_id=7f3a");
+//! ```
+
+use std::cell::RefCell;
+use std::fmt::Display;
+use std::marker::PhantomData;
+
+thread_local! {
+    static STACK: RefCell<Vec<(String, String)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// `ContextGuard` removes a field from the context when dropped.
+///
+/// Guards are tied to the thread that created them and should be dropped in the
+/// reverse order of their creation; dropping one also removes the fields pushed
+/// after it.
+#[must_use = "the field is removed from the context as soon as the guard is dropped"]
+#[derive(Debug)]
+pub struct ContextGuard {
+    depth: usize,
+    // The context is thread-local, so the guard must not move to another thread.
+    _thread: PhantomData<*const ()>,
+}
+
+impl Drop for ContextGuard {
+    fn drop(&mut self) {
+        STACK.with(|stack| stack.borrow_mut().truncate(self.depth));
+    }
+}
+
+/// Push `key` with `value` on the context of the current thread, until the returned
+/// guard is dropped.
+pub fn push<V: Display>(key: &str, value: V) -> ContextGuard {
+    let depth = STACK.with(|stack| {
+        let mut stack = stack.borrow_mut();
+        stack.push((key.to_owned(), value.to_string()));
+        stack.len() - 1
+    });
+    ContextGuard { depth, _thread: PhantomData }
+}
+
+/// Run `f` with `key` set to `value` on the context of the current thread.
+pub fn with_context<V, F, R>(key: &str, value: V, f: F) -> R
+where
+    V: Display,
+    F: FnOnce() -> R,
+{
+    let _guard = push(key, value);
+    f()
+}
+
+/// The fields of the current context in the order they were first pushed, each
+/// with its innermost value.
+pub fn fields() -> Vec<(String, String)> {
+    STACK.with(|stack| {
+        let stack = stack.borrow();
+        let mut fields: Vec<(String, String)> = Vec::with_capacity(stack.len());
+        for (key, value) in stack.iter() {
+            match fields.iter_mut().find(|(k, _)| k == key) {
+                Some(field) => field.1 = value.clone(),
+                None => fields.push((key.clone(), value.clone())),
+            }
+        }
+        fields
+    })
+}
+
+/// Render context `fields` as space-separated `key=value` pairs.
+pub(crate) fn text(fields: &[(String, String)]) -> String {
+    let mut text = String::new();
+    for (key, value) in fields {
+        crate::fields::push_pair(&mut text, key, value);
+    }
+    text
+}
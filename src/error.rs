@@ -0,0 +1,178 @@
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// `OnError` is used to choose what happens when a record cannot be written.
+///
+/// Logging never panics because of a full disk or a closed terminal: the record is
+/// lost for that destination, counted in [`failed_writes`](fn.failed_writes.html),
+/// and the error is handled according to the policy.
+///
+/// ## Example
+///
+/// ```no_run
+/// # extern crate loge;
+/// loge::Builder::new()
+///     .file("app.log")
+///     .on_error(loge::OnError::call(|e| eprintln!("cannot write log: {}", e)))
+///     .init();
+/// ```
+#[derive(Clone, Default)]
+pub enum OnError {
+    /// discard the error silently
+    Ignore,
+    /// print the first error to stderr, and stay quiet afterwards
+    #[default]
+    ReportOnce,
+    /// write the records that failed to another destination
+    Fallback(Fallback),
+    /// hand every error to a user-supplied function
+    Call(Arc<dyn Fn(&io::Error) + Send + Sync>),
+}
+
+impl OnError {
+    /// Hand every error to `f`.
+    pub fn call<F>(f: F) -> OnError
+    where
+        F: Fn(&io::Error) + Send + Sync + 'static,
+    {
+        OnError::Call(Arc::new(f))
+    }
+}
+
+impl fmt::Debug for OnError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OnError::Ignore => f.write_str("Ignore"),
+            OnError::ReportOnce => f.write_str("ReportOnce"),
+            OnError::Fallback(fallback) => f.debug_tuple("Fallback").field(fallback).finish(),
+            OnError::Call(_) => f.write_str("Call(..)"),
+        }
+    }
+}
+
+/// `Fallback` is used to choose where records go when their destination fails.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fallback {
+    /// standard error
+    Stderr,
+    /// a file, opened for appending on the first failure
+    File(PathBuf),
+}
+
+/// `FailedWrites` counts the records that could not be written, per destination.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FailedWrites {
+    /// records lost by the log file, including failed rotations and flushes
+    pub file: u64,
+    /// records lost by the terminal
+    pub console: u64,
+    /// records lost by sinks provided by the application
+    pub other: u64,
+}
+
+impl FailedWrites {
+    /// Failed writes over every destination.
+    pub fn total(&self) -> u64 {
+        self.file + self.console + self.other
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Destination {
+    File,
+    Console,
+    Other,
+}
+
+impl fmt::Display for Destination {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Destination::File => "the log file",
+            Destination::Console => "the terminal",
+            Destination::Other => "a custom sink",
+        })
+    }
+}
+
+/// Applies the error policy and counts the failures.
+pub(crate) struct ErrorHandler {
+    policy: OnError,
+    file: AtomicU64,
+    console: AtomicU64,
+    other: AtomicU64,
+    reported: AtomicBool,
+    fallback: Mutex<Option<File>>,
+}
+
+impl ErrorHandler {
+    pub fn new(policy: OnError) -> ErrorHandler {
+        ErrorHandler {
+            policy,
+            file: AtomicU64::new(0),
+            console: AtomicU64::new(0),
+            other: AtomicU64::new(0),
+            reported: AtomicBool::new(false),
+            fallback: Mutex::new(None),
+        }
+    }
+
+    /// Handle `error`, raised while writing `record` (if any) to `destination`.
+    pub fn failed(&self, destination: Destination, error: &io::Error, record: Option<&str>) {
+        match destination {
+            Destination::File => self.file.fetch_add(1, Ordering::Relaxed),
+            Destination::Console => self.console.fetch_add(1, Ordering::Relaxed),
+            Destination::Other => self.other.fetch_add(1, Ordering::Relaxed),
+        };
+        match self.policy {
+            OnError::Ignore => {}
+            OnError::ReportOnce => self.report_once(destination, error),
+            OnError::Fallback(ref fallback) => {
+                let record = match record {
+                    Some(record) => record,
+                    None => return,
+                };
+                if let Err(e) = self.write_fallback(fallback, record) {
+                    self.report_once(destination, &e);
+                }
+            }
+            OnError::Call(ref f) => f(error),
+        }
+    }
+
+    pub fn counts(&self) -> FailedWrites {
+        FailedWrites {
+            file: self.file.load(Ordering::Relaxed),
+            console: self.console.load(Ordering::Relaxed),
+            other: self.other.load(Ordering::Relaxed),
+        }
+    }
+
+    fn report_once(&self, destination: Destination, error: &io::Error) {
+        if !self.reported.swap(true, Ordering::Relaxed) {
+            eprintln!(
+                "warning: failed to write a log record to {}: {}; further errors are not reported",
+                destination, error
+            );
+        }
+    }
+
+    fn write_fallback(&self, fallback: &Fallback, record: &str) -> io::Result<()> {
+        match fallback {
+            Fallback::Stderr => writeln!(io::stderr().lock(), "{}", record),
+            Fallback::File(path) => {
+                let mut file = self.fallback.lock().unwrap_or_else(|e| e.into_inner());
+                if file.is_none() {
+                    *file = Some(OpenOptions::new().create(true).append(true).open(path)?);
+                }
+                match file.as_mut() {
+                    Some(file) => writeln!(file, "{}", record),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,213 @@
+#[cfg(feature = "kv")]
+use log::kv::{self, Key, Value, VisitSource, VisitValue};
+use log::Record;
+use std::fmt::Write;
+
+/// Render the key-values of `record` as space-separated `key=value` pairs, quoting
+/// values that are empty or contain whitespace, quotes or `=`.
+pub(crate) fn text(record: &Record) -> String {
+    #[cfg(feature = "kv")]
+    {
+        struct Text(String);
+
+        impl<'kvs> VisitSource<'kvs> for Text {
+            fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), kv::Error> {
+                push_pair(&mut self.0, key.as_str(), &value.to_string());
+                Ok(())
+            }
+        }
+
+        let mut text = Text(String::new());
+        let _ = record.key_values().visit(&mut text);
+        text.0
+    }
+    #[cfg(not(feature = "kv"))]
+    {
+        let _ = record;
+        String::new()
+    }
+}
+
+/// Collect the key-values of `record` as strings.
+pub(crate) fn pairs(record: &Record) -> Vec<(String, String)> {
+    #[cfg(feature = "kv")]
+    {
+        struct Pairs(Vec<(String, String)>);
+
+        impl<'kvs> VisitSource<'kvs> for Pairs {
+            fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), kv::Error> {
+                self.0.push((key.to_string(), value.to_string()));
+                Ok(())
+            }
+        }
+
+        let mut pairs = Pairs(Vec::new());
+        let _ = record.key_values().visit(&mut pairs);
+        pairs.0
+    }
+    #[cfg(not(feature = "kv"))]
+    {
+        let _ = record;
+        Vec::new()
+    }
+}
+
+/// Append `key=value` to `text`, after a space unless `text` is empty.
+pub(crate) fn push_pair(text: &mut String, key: &str, value: &str) {
+    if !text.is_empty() {
+        text.push(' ');
+    }
+    let quote =
+        value.is_empty() || value.chars().any(|c| c.is_whitespace() || c == '"' || c == '=');
+    if quote {
+        let _ = write!(text, "{}={:?}", key, value);
+    } else {
+        let _ = write!(text, "{}={}", key, value);
+    }
+}
+
+/// Write `key=value` to `buf` in logfmt: characters which cannot appear in a key
+/// are replaced by `_`, and values which are empty or contain spaces, `=`, quotes,
+/// backslashes or control characters are quoted, with quotes, backslashes and
+/// control characters escaped.
+pub(crate) fn push_logfmt(buf: &mut String, key: &str, value: &str) {
+    if !buf.is_empty() {
+        buf.push(' ');
+    }
+    if key.is_empty() {
+        buf.push('_');
+    }
+    buf.extend(key.chars().map(|c| match c {
+        '=' | '"' | '\\' => '_',
+        c if c.is_whitespace() || c.is_control() => '_',
+        c => c,
+    }));
+    buf.push('=');
+    let quote = value.is_empty()
+        || value
+            .chars()
+            .any(|c| c == '=' || c == '"' || c == '\\' || c.is_whitespace() || c.is_control());
+    if !quote {
+        buf.push_str(value);
+        return;
+    }
+    buf.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => buf.push_str("\\\""),
+            '\\' => buf.push_str("\\\\"),
+            '\n' => buf.push_str("\\n"),
+            '\r' => buf.push_str("\\r"),
+            '\t' => buf.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(buf, "\\u{:04x}", c as u32);
+            }
+            c => buf.push(c),
+        }
+    }
+    buf.push('"');
+}
+
+/// Append the thread's `context` and the key-values of `record` to `message`,
+/// separated by a space.
+pub(crate) fn append(mut message: String, record: &Record, context: &[(String, String)]) -> String {
+    for fields in [crate::context::text(context), text(record)] {
+        if !fields.is_empty() {
+            message.push(' ');
+            message.push_str(&fields);
+        }
+    }
+    message
+}
+
+/// Collect the key-values of `record` into a JSON object, keeping numbers, booleans
+/// and nulls as native JSON values.
+#[cfg(feature = "json")]
+pub(crate) fn json(record: &Record) -> serde_json::Map<String, serde_json::Value> {
+    #[cfg(feature = "kv")]
+    {
+        use serde_json::{Map, Number, Value as Json};
+        use std::convert::TryFrom;
+
+        struct Object(Map<String, Json>);
+
+        impl<'kvs> VisitSource<'kvs> for Object {
+            fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), kv::Error> {
+                let mut json = Native(Json::Null);
+                value.visit(&mut json)?;
+                self.0.insert(key.to_string(), json.0);
+                Ok(())
+            }
+        }
+
+        struct Native(Json);
+
+        impl<'v> VisitValue<'v> for Native {
+            fn visit_any(&mut self, value: Value) -> Result<(), kv::Error> {
+                self.0 = Json::String(value.to_string());
+                Ok(())
+            }
+
+            fn visit_null(&mut self) -> Result<(), kv::Error> {
+                self.0 = Json::Null;
+                Ok(())
+            }
+
+            fn visit_u64(&mut self, value: u64) -> Result<(), kv::Error> {
+                self.0 = Json::from(value);
+                Ok(())
+            }
+
+            fn visit_i64(&mut self, value: i64) -> Result<(), kv::Error> {
+                self.0 = Json::from(value);
+                Ok(())
+            }
+
+            fn visit_u128(&mut self, value: u128) -> Result<(), kv::Error> {
+                self.0 = match u64::try_from(value) {
+                    Ok(value) => Json::from(value),
+                    Err(_) => Json::String(value.to_string()),
+                };
+                Ok(())
+            }
+
+            fn visit_i128(&mut self, value: i128) -> Result<(), kv::Error> {
+                self.0 = match i64::try_from(value) {
+                    Ok(value) => Json::from(value),
+                    Err(_) => Json::String(value.to_string()),
+                };
+                Ok(())
+            }
+
+            fn visit_f64(&mut self, value: f64) -> Result<(), kv::Error> {
+                // NaN and infinities have no JSON representation.
+                self.0 = Number::from_f64(value).map_or(Json::Null, Json::Number);
+                Ok(())
+            }
+
+            fn visit_bool(&mut self, value: bool) -> Result<(), kv::Error> {
+                self.0 = Json::Bool(value);
+                Ok(())
+            }
+
+            fn visit_str(&mut self, value: &str) -> Result<(), kv::Error> {
+                self.0 = Json::String(value.to_owned());
+                Ok(())
+            }
+
+            fn visit_char(&mut self, value: char) -> Result<(), kv::Error> {
+                self.0 = Json::String(value.to_string());
+                Ok(())
+            }
+        }
+
+        let mut object = Object(Map::new());
+        let _ = record.key_values().visit(&mut object);
+        object.0
+    }
+    #[cfg(not(feature = "kv"))]
+    {
+        let _ = record;
+        serde_json::Map::new()
+    }
+}
@@ -1,34 +1,440 @@
-use std::fs::{File, OpenOptions};
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Duration as ChronoDuration, Local, Timelike};
+use std::ffi::OsStr;
+use std::fs::{self, File, OpenOptions};
 use std::io;
 use std::io::BufWriter;
 use std::io::Write as IoWrite;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+use crate::compressor::{Compression, Compressor};
+use crate::reopen;
+
+// How often the path is checked when reopening moved files.
+const MOVED_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// `Period` is used to rotate a log file on clock boundaries (local time).
+#[cfg(feature = "chrono")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Period {
+    /// rotate at the start of every hour
+    Hourly,
+    /// rotate at midnight
+    Daily,
+}
+
+/// `Naming` is used to choose how rotated segments of `app.log` are named.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Naming {
+    /// `app.log.1` for the newest segment, `app.log.2` for the one before, etc.
+    #[default]
+    Numbered,
+    /// `app-2026-10-18.log`, named after the period the segment covers (with the
+    /// hour as well for hourly rotation); a `.1`, `.2`... suffix is added before the
+    /// extension if several segments cover the same period.
+    #[cfg(feature = "chrono")]
+    Dated,
+}
+
+/// `Rotation` describes when a log file is rotated and which segments are kept.
+///
+/// A file is rotated when it would exceed `max_bytes`, or when a new `period`
+/// starts, whichever comes first. By default nothing is ever rotated.
+///
+/// ## Example
+///
+/// ```no_run
+/// # extern crate loge;
+/// use std::time::Duration;
+///
+/// let rotation = loge::Rotation::new()
+///     .max_bytes(64 * 1024 * 1024)
+///     .period(loge::Period::Daily)
+///     .naming(loge::Naming::Dated)
+///     .keep(14)
+///     .max_age(Duration::from_secs(30 * 24 * 3600));
+/// loge::Builder::new().file("app.log").rotation(rotation).init();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Rotation {
+    max_bytes: Option<u64>,
+    #[cfg(feature = "chrono")]
+    period: Option<Period>,
+    naming: Naming,
+    keep: Option<usize>,
+    max_age: Option<Duration>,
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    compression: Option<Compression>,
+}
+
+impl Rotation {
+    /// Create a policy that never rotates.
+    pub fn new() -> Rotation {
+        Rotation::default()
+    }
+
+    /// Rotate before the file grows beyond `bytes`.
+    pub fn max_bytes(mut self, bytes: u64) -> Rotation {
+        self.max_bytes = Some(bytes);
+        self
+    }
+
+    /// Rotate when a new `period` starts.
+    #[cfg(feature = "chrono")]
+    pub fn period(mut self, period: Period) -> Rotation {
+        self.period = Some(period);
+        self
+    }
+
+    /// Set how rotated segments are named.
+    pub fn naming(mut self, naming: Naming) -> Rotation {
+        self.naming = naming;
+        self
+    }
+
+    /// Keep at most `count` rotated segments, deleting the oldest ones.
+    pub fn keep(mut self, count: usize) -> Rotation {
+        self.keep = Some(count);
+        self
+    }
+
+    /// Delete rotated segments last modified more than `age` ago.
+    pub fn max_age(mut self, age: Duration) -> Rotation {
+        self.max_age = Some(age);
+        self
+    }
+
+    /// Compress rotated segments on a background thread.
+    ///
+    /// Compressed segments keep their name with an extra extension, such as
+    /// `app.log.1.gz`, and still count towards `keep` and `max_age`.
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    pub fn compress(mut self, compression: Compression) -> Rotation {
+        self.compression = Some(compression);
+        self
+    }
+}
 
 #[derive(Debug)]
 pub struct FileWriter {
     /// The path to the logging file.
     path: PathBuf,
-    writer: BufWriter<File>,
+    /// `None` while the file cannot be opened, reopened on the next write.
+    writer: Option<BufWriter<File>>,
+    rotation: Rotation,
+    /// Bytes in the active segment.
+    size: u64,
+    /// Start of the period covered by the active segment.
+    #[cfg(feature = "chrono")]
+    opened: DateTime<Local>,
+    /// Number of rotations so far, locked while segments are renamed.
+    generation: Arc<Mutex<u64>>,
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    compressor: Option<Compressor>,
+    /// Reopen requests handled so far.
+    reopens: u64,
+    /// When the path was last checked for a moved file, if that is enabled.
+    moved_check: Option<Instant>,
 }
 
 impl FileWriter {
-    pub fn new(path: PathBuf) -> FileWriter {
-        let file =
-            OpenOptions::new().write(true).append(true).create(true).open(path.as_path()).unwrap();
+    pub fn new(path: PathBuf, rotation: Rotation) -> FileWriter {
+        // An error opening the file is reported by the first write.
+        let file = open(&path).ok();
+        let metadata = file.as_ref().and_then(|file| file.metadata().ok());
+        let size = metadata.as_ref().map_or(0, |m| m.len());
+        // An existing segment covers the period it was last written in.
+        #[cfg(feature = "chrono")]
+        let opened = match metadata.and_then(|m| m.modified().ok()) {
+            Some(modified) if size > 0 => DateTime::from(modified),
+            _ => Local::now(),
+        };
+
+        let generation = Arc::new(Mutex::new(0));
+        #[cfg(any(feature = "gzip", feature = "zstd"))]
+        let compressor = rotation.compression.map(|compression| {
+            Compressor::new(path.clone(), rotation.naming, compression, generation.clone())
+        });
 
-        FileWriter { path, writer: BufWriter::new(file) }
+        FileWriter {
+            path,
+            writer: file.map(BufWriter::new),
+            rotation,
+            size,
+            #[cfg(feature = "chrono")]
+            opened,
+            generation,
+            #[cfg(any(feature = "gzip", feature = "zstd"))]
+            compressor,
+            reopens: reopen::requests(),
+            moved_check: None,
+        }
+    }
+
+    /// Reopen the file when the path no longer points to it, e.g. after an external
+    /// tool renamed or deleted it. The path is checked at most once per second.
+    pub fn reopen_when_moved(&mut self) {
+        self.moved_check = Some(Instant::now());
     }
 
-    pub fn write(&mut self, record: String) -> io::Result<()> {
-        let writer = self.writer.get_mut();
-        let result = writeln!(writer, "{}", record);
-        if result.is_err() {
-            return result;
+    /// Close the file and open the path again, e.g. after an external tool renamed it.
+    pub fn reopen(&mut self) -> io::Result<()> {
+        self.reopens = reopen::requests();
+        // Flush the previous file here, as dropping a `BufWriter` ignores errors. The
+        // next write opens the path again if this fails.
+        if let Some(mut writer) = self.writer.take() {
+            writer.flush()?;
         }
+        let file = open(&self.path)?;
+        self.size = file.metadata()?.len();
+        self.writer = Some(BufWriter::new(file));
         Ok(())
     }
 
-    pub fn flush(&self) -> io::Result<()> {
+    // Whether the path points to another file than the open one.
+    fn moved(&mut self) -> bool {
+        match self.moved_check {
+            Some(checked) if checked.elapsed() >= MOVED_CHECK_INTERVAL => {
+                self.moved_check = Some(Instant::now())
+            }
+            _ => return false,
+        }
+        let open = match self.writer {
+            Some(ref writer) => writer.get_ref().metadata(),
+            None => return false,
+        };
+        match (open, fs::metadata(&self.path)) {
+            (Ok(open), Ok(current)) => !same_file(&open, &current),
+            (Ok(_), Err(ref e)) => e.kind() == io::ErrorKind::NotFound,
+            _ => false,
+        }
+    }
+
+    /// Write `record` as a line, rotating the file first if the policy asks for it.
+    ///
+    /// The writer is shared behind a lock, so a rotation never interleaves with
+    /// writes from other threads.
+    pub fn write(&mut self, record: &str) -> io::Result<()> {
+        if self.reopens != reopen::requests() || self.moved() {
+            self.reopen()?;
+        }
+        let len = record.len() as u64 + 1;
+        if self.should_rotate(len) {
+            self.rotate()?;
+        }
+        let writer = match self.writer {
+            Some(ref mut writer) => writer.get_mut(),
+            None => {
+                let file = open(&self.path)?;
+                self.writer.get_or_insert(BufWriter::new(file)).get_mut()
+            }
+        };
+        writeln!(writer, "{}", record)?;
+        self.size += len;
         Ok(())
     }
+
+    /// Flush buffered data and ask the OS to persist the file.
+    pub fn flush(&mut self) -> io::Result<()> {
+        match self.writer {
+            Some(ref mut writer) => {
+                writer.flush()?;
+                writer.get_ref().sync_data()
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn should_rotate(&self, len: u64) -> bool {
+        if let Some(max_bytes) = self.rotation.max_bytes {
+            if self.size > 0 && self.size + len > max_bytes {
+                return true;
+            }
+        }
+        #[cfg(feature = "chrono")]
+        {
+            if let Some(period) = self.rotation.period {
+                return Local::now() >= next_period(self.opened, period);
+            }
+        }
+        false
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        if let Some(mut writer) = self.writer.take() {
+            writer.flush()?;
+        }
+        let generation = self.generation.clone();
+        let mut generation = generation.lock().unwrap_or_else(|e| e.into_inner());
+        let closed = match self.rotation.naming {
+            Naming::Numbered => {
+                let prefix = self.file_name();
+                let mut segments = self.segments()?;
+                // Shift the oldest segments first so no name is overwritten.
+                segments
+                    .sort_by_key(|(_, name, _)| std::cmp::Reverse(split_index(&prefix, name).0));
+                for (path, name, _) in segments {
+                    let (index, suffix) = split_index(&prefix, &name);
+                    let renamed = format!("{}.{}{}", prefix, index + 1, suffix);
+                    fs::rename(&path, path.with_file_name(renamed))?;
+                }
+                let closed = self.path.with_file_name(format!("{}.1", prefix));
+                fs::rename(&self.path, &closed)?;
+                closed
+            }
+            #[cfg(feature = "chrono")]
+            Naming::Dated => {
+                let date = match self.rotation.period {
+                    Some(Period::Hourly) => self.opened.format("%Y-%m-%d-%H"),
+                    _ => self.opened.format("%Y-%m-%d"),
+                };
+                let (stem, extension) = self.stem_and_extension();
+                let mut target =
+                    self.path.with_file_name(format!("{}-{}{}", stem, date, extension));
+                let mut n = 0;
+                while self.segments()?.iter().any(|(path, _, _)| same_segment(path, &target)) {
+                    n += 1;
+                    target =
+                        self.path.with_file_name(format!("{}-{}.{}{}", stem, date, n, extension));
+                }
+                fs::rename(&self.path, &target)?;
+                target
+            }
+        };
+        *generation += 1;
+
+        self.writer = Some(BufWriter::new(open(&self.path)?));
+        self.size = 0;
+        #[cfg(feature = "chrono")]
+        {
+            self.opened = Local::now();
+        }
+        #[cfg(any(feature = "gzip", feature = "zstd"))]
+        {
+            if let Some(ref compressor) = self.compressor {
+                compressor.compress(closed.clone(), *generation);
+            }
+        }
+        #[cfg(not(any(feature = "gzip", feature = "zstd")))]
+        let _ = closed;
+        self.prune()
+    }
+
+    // Delete the segments beyond `keep` or older than `max_age`.
+    fn prune(&self) -> io::Result<()> {
+        if self.rotation.keep.is_none() && self.rotation.max_age.is_none() {
+            return Ok(());
+        }
+        let mut segments = self.segments()?;
+        // Newest first; numbered segments are ordered by their index since several
+        // may share the same modification time.
+        match self.rotation.naming {
+            Naming::Numbered => {
+                let prefix = self.file_name();
+                segments.sort_by_key(|(_, name, _)| split_index(&prefix, name).0);
+            }
+            #[cfg(feature = "chrono")]
+            Naming::Dated => segments.sort_by(|a, b| (b.2, &b.1).cmp(&(a.2, &a.1))),
+        }
+        let now = SystemTime::now();
+        for (i, (path, _, modified)) in segments.into_iter().enumerate() {
+            let too_many = self.rotation.keep.is_some_and(|keep| i >= keep);
+            let too_old = self
+                .rotation
+                .max_age
+                .is_some_and(|max_age| now.duration_since(modified).is_ok_and(|age| age > max_age));
+            if too_many || too_old {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+
+    // The rotated segments of this file, with their names and modification times.
+    fn segments(&self) -> io::Result<Vec<(PathBuf, String, SystemTime)>> {
+        let dir = match self.path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        let mut segments = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let name = match entry.file_name().into_string() {
+                Ok(name) => name,
+                Err(_) => continue,
+            };
+            if self.is_segment(&name) {
+                segments.push((entry.path(), name, entry.metadata()?.modified()?));
+            }
+        }
+        Ok(segments)
+    }
+
+    // Whether `name` looks like a rotated segment of this file, possibly compressed.
+    fn is_segment(&self, name: &str) -> bool {
+        let after = |prefix: &str| {
+            name.starts_with(prefix)
+                && name[prefix.len()..].starts_with(|c: char| c.is_ascii_digit())
+        };
+        match self.rotation.naming {
+            Naming::Numbered => after(&format!("{}.", self.file_name())),
+            #[cfg(feature = "chrono")]
+            Naming::Dated => after(&format!("{}-", self.stem_and_extension().0)),
+        }
+    }
+
+    fn file_name(&self) -> String {
+        self.path.file_name().and_then(OsStr::to_str).unwrap_or_default().to_owned()
+    }
+
+    // Split `app.log` into `app` and `.log`.
+    #[cfg(feature = "chrono")]
+    fn stem_and_extension(&self) -> (String, String) {
+        let stem = self.path.file_stem().and_then(OsStr::to_str).unwrap_or_default();
+        let extension = self.path.extension().and_then(OsStr::to_str);
+        (stem.to_owned(), extension.map(|e| format!(".{}", e)).unwrap_or_default())
+    }
+}
+
+fn open(path: &Path) -> io::Result<File> {
+    OpenOptions::new().append(true).create(true).open(path)
+}
+
+// Whether both metadata describe the same file.
+#[cfg(unix)]
+fn same_file(a: &fs::Metadata, b: &fs::Metadata) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    a.dev() == b.dev() && a.ino() == b.ino()
+}
+
+#[cfg(not(unix))]
+fn same_file(_: &fs::Metadata, _: &fs::Metadata) -> bool {
+    true
+}
+
+// Split a numbered segment name such as `app.log.3.gz` into `3` and `.gz`.
+fn split_index<'a>(prefix: &str, name: &'a str) -> (usize, &'a str) {
+    let rest = &name[prefix.len() + 1..];
+    let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+    (rest[..digits].parse().unwrap_or(0), &rest[digits..])
+}
+
+// Whether `existing` is `target`, possibly with a compression suffix.
+#[cfg(feature = "chrono")]
+fn same_segment(existing: &Path, target: &Path) -> bool {
+    existing == target || existing.file_stem() == target.file_name()
+}
+
+// The start of the period following the one `opened` belongs to.
+#[cfg(feature = "chrono")]
+fn next_period(opened: DateTime<Local>, period: Period) -> DateTime<Local> {
+    let start = opened.with_minute(0).and_then(|t| t.with_second(0)).unwrap_or(opened);
+    let start = start.with_nanosecond(0).unwrap_or(start);
+    match period {
+        Period::Hourly => start + ChronoDuration::hours(1),
+        Period::Daily => start.with_hour(0).unwrap_or(start) + ChronoDuration::days(1),
+    }
 }
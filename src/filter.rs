@@ -0,0 +1,148 @@
+use log::{LevelFilter, Metadata, Record};
+
+// A level applying to every target below `name`, or to all of them when `name` is `None`.
+#[derive(Debug, Clone)]
+struct Directive {
+    name: Option<String>,
+    level: LevelFilter,
+}
+
+// Filter applied to the formatted message of a record.
+#[derive(Debug, Clone)]
+struct MessageFilter {
+    #[cfg(feature = "regex")]
+    inner: regex::Regex,
+    #[cfg(not(feature = "regex"))]
+    inner: String,
+}
+
+impl MessageFilter {
+    fn new(spec: &str) -> Option<MessageFilter> {
+        #[cfg(feature = "regex")]
+        {
+            match regex::Regex::new(spec) {
+                Ok(inner) => Some(MessageFilter { inner }),
+                Err(e) => {
+                    eprintln!("warning: invalid regex filter - {}", e);
+                    None
+                }
+            }
+        }
+        #[cfg(not(feature = "regex"))]
+        {
+            Some(MessageFilter { inner: spec.to_owned() })
+        }
+    }
+
+    fn is_match(&self, message: &str) -> bool {
+        #[cfg(feature = "regex")]
+        {
+            self.inner.is_match(message)
+        }
+        #[cfg(not(feature = "regex"))]
+        {
+            message.contains(&self.inner)
+        }
+    }
+}
+
+/// Level filters in the same syntax as `env_logger`.
+///
+/// A spec is a comma-separated list of directives, optionally followed by `/` and
+/// a message filter: `info,my_crate=debug,hyper=off/timeout`. A directive is either
+/// a bare level, which sets the default, a bare module path, which enables every
+/// level for it, or `path=level`. Level names are case-insensitive. A record is
+/// checked against the directive with the longest path that is its target or one
+/// of its parent modules.
+///
+/// The message filter is a regular expression when the `regex` feature is enabled
+/// and a plain substring otherwise.
+#[derive(Debug, Clone)]
+pub(crate) struct Filter {
+    directives: Vec<Directive>,
+    message: Option<MessageFilter>,
+}
+
+impl Filter {
+    /// Create a filter logging `level` and above for every target.
+    pub fn new(level: LevelFilter) -> Filter {
+        Filter { directives: vec![Directive { name: None, level }], message: None }
+    }
+
+    /// Add the directives of a spec such as `warn,my_crate::db=trace/slow query`,
+    /// replacing existing ones for the same path.
+    ///
+    /// Invalid directives are reported on stderr and skipped.
+    pub fn extend(&mut self, spec: &str) {
+        let mut parts = spec.splitn(2, '/');
+        let directives = parts.next().unwrap_or_default();
+        if let Some(message) = parts.next() {
+            if message.contains('/') {
+                eprintln!("warning: invalid logging spec '{}', ignoring it", spec);
+                return;
+            }
+            self.message = MessageFilter::new(message);
+        }
+
+        for directive in directives.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let mut parts = directive.splitn(2, '=');
+            let (name, level) = (parts.next().unwrap_or_default(), parts.next());
+            match level {
+                None => match name.parse() {
+                    Ok(level) => self.insert(None, level),
+                    Err(_) => self.insert(Some(name), LevelFilter::Trace),
+                },
+                Some(level) => match level.trim().parse() {
+                    Ok(level) if name.trim().is_empty() => self.insert(None, level),
+                    Ok(level) => self.insert(Some(name.trim()), level),
+                    Err(_) => {
+                        eprintln!("warning: invalid logging spec '{}', ignoring it", directive)
+                    }
+                },
+            }
+        }
+    }
+
+    /// Set the level of `name`, or the default level if `name` is `None`.
+    pub fn insert(&mut self, name: Option<&str>, level: LevelFilter) {
+        let name = name.map(str::to_owned);
+        match self.directives.iter_mut().find(|d| d.name == name) {
+            Some(directive) => directive.level = level,
+            None => self.directives.push(Directive { name, level }),
+        }
+    }
+
+    /// The most verbose level any target may log at.
+    pub fn max_level(&self) -> LevelFilter {
+        self.directives.iter().map(|d| d.level).max().unwrap_or(LevelFilter::Off)
+    }
+
+    /// Whether a record with this metadata passes the level directives.
+    pub fn enabled(&self, metadata: &Metadata) -> bool {
+        let target = metadata.target();
+        let directive = self
+            .directives
+            .iter()
+            .filter(|d| match &d.name {
+                None => true,
+                Some(name) => {
+                    target == name
+                        || (target.starts_with(name.as_str())
+                            && target[name.len()..].starts_with("::"))
+                }
+            })
+            .max_by_key(|d| d.name.as_ref().map_or(0, |name| name.len() + 1));
+        directive.is_some_and(|d| metadata.level() <= d.level)
+    }
+
+    /// Whether `record` passes both the level directives and the message filter.
+    pub fn matches(&self, record: &Record) -> bool {
+        if !self.enabled(record.metadata()) {
+            return false;
+        }
+        match &self.message {
+            Some(message) => message.is_match(&record.args().to_string()),
+            None => true,
+        }
+    }
+}
@@ -0,0 +1,462 @@
+use crate::color::Painted;
+use crate::theme::{Style, Theme};
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Local};
+use log::{Level, Record};
+use std::io::{self, Write};
+use std::time::SystemTime;
+
+/// `Formatter` lays out records, for use with
+/// [`LogeFormat::Custom`](enum.LogeFormat.html#variant.Custom).
+///
+/// Besides the record, formatters get a [`FormatContext`](struct.FormatContext.html)
+/// with the time of the record, the [context](context/index.html) of the logging
+/// thread and the theme, which stay the same for every output the record goes to.
+/// `loge` hands every formatter an empty buffer which it reuses from one record to
+/// the next, so formatters need no buffer of their own. The built-in layouts are
+/// formatters too, and can be wrapped.
+///
+/// ## Example
+///
+/// ```
+/// # extern crate loge;
+/// use std::io::{self, Write};
+///
+/// // `LEVEL target: message`, e.g. for a test harness capturing lines.
+/// struct Short;
+///
+/// impl loge::Formatter for Short {
+///     fn format(
+///         &self,
+///         record: &log::Record,
+///         cx: &loge::FormatContext,
+///         buf: &mut Vec<u8>,
+///     ) -> io::Result<()> {
+///         match cx.theme() {
+///             Some(theme) => write!(buf, "{}", theme.level_style(record.level()).paint(record.level()))?,
+///             None => write!(buf, "{}", record.level())?,
+///         }
+///         write!(buf, " {}: {}", record.target(), record.args())
+///     }
+/// }
+///
+/// loge::Builder::new().format(loge::LogeFormat::Custom(Box::new(Short))).init();
+/// ```
+pub trait Formatter: Send + Sync {
+    /// Write `record` to `buf` as one line, without its trailing newline.
+    ///
+    /// An error drops the record for the outputs using this formatter, and is
+    /// handled according to the [`OnError`](enum.OnError.html) policy.
+    fn format(&self, record: &Record, cx: &FormatContext, buf: &mut Vec<u8>) -> io::Result<()>;
+}
+
+/// `FormatContext` is what a [`Formatter`](trait.Formatter.html) gets besides the
+/// record: when it was logged, the context of the logging thread, and the theme
+/// when the output expects ANSI colors.
+#[derive(Debug, Clone, Copy)]
+pub struct FormatContext<'a> {
+    time: SystemTime,
+    context: &'a [(String, String)],
+    theme: Option<&'a Theme>,
+}
+
+impl<'a> FormatContext<'a> {
+    /// Create a context for a record logged at `time`, with the `context` fields of
+    /// the logging thread, such as [`context::fields`](context/fn.fields.html)
+    /// returns, and the `theme` to color it with, if any.
+    pub fn new(
+        time: SystemTime,
+        context: &'a [(String, String)],
+        theme: Option<&'a Theme>,
+    ) -> FormatContext<'a> {
+        FormatContext { time, context, theme }
+    }
+
+    /// When the record was logged.
+    pub fn time(&self) -> SystemTime {
+        self.time
+    }
+
+    /// The context fields of the logging thread, in the order they were pushed.
+    pub fn context(&self) -> &'a [(String, String)] {
+        self.context
+    }
+
+    /// The theme to color the line with, given when the output expects ANSI
+    /// colors.
+    pub fn theme(&self) -> Option<&'a Theme> {
+        self.theme
+    }
+}
+
+/// `TargetFormatter` is the `target` layout:
+/// `date time [level] target ... message`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TargetFormatter;
+
+impl Formatter for TargetFormatter {
+    fn format(&self, record: &Record, cx: &FormatContext, buf: &mut Vec<u8>) -> io::Result<()> {
+        let theme = cx.theme();
+        let msg = crate::fields::append(crate::error_trace_message(record), record, cx.context());
+        write_time(buf, cx.time(), theme)?;
+        write_level(buf, record.level(), theme)?;
+        write!(
+            buf,
+            "{} ... {}",
+            paint(theme, Theme::target_style, crate::record_target(record)),
+            msg
+        )
+    }
+}
+
+/// `FilelineFormatter` is the `fileline` layout:
+/// `date time [level] file - target (line n) ... message`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FilelineFormatter;
+
+impl Formatter for FilelineFormatter {
+    fn format(&self, record: &Record, cx: &FormatContext, buf: &mut Vec<u8>) -> io::Result<()> {
+        let theme = cx.theme();
+        let msg = crate::fields::append(crate::error_trace_message(record), record, cx.context());
+        let line = record.line().map_or(-1, |v| v as i32).to_string();
+        write_time(buf, cx.time(), theme)?;
+        write_level(buf, record.level(), theme)?;
+        write!(
+            buf,
+            "{} - {} (line {}) ... {}",
+            paint(theme, Theme::file_style, record.file().unwrap_or("<unknown>")),
+            paint(theme, Theme::target_style, crate::record_target(record)),
+            paint(theme, Theme::line_style, line),
+            msg
+        )
+    }
+}
+
+/// `JsonFormatter` is the `json` layout: one object per line, with the time,
+/// level, message, service, location, context and key-values of the record.
+#[cfg(feature = "json")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct JsonFormatter;
+
+#[cfg(feature = "json")]
+impl Formatter for JsonFormatter {
+    fn format(&self, record: &Record, cx: &FormatContext, buf: &mut Vec<u8>) -> io::Result<()> {
+        use serde_json::json;
+
+        let theme = cx.theme();
+        let name = crate::service_name();
+        let version = crate::service_version();
+
+        let line = record.line().map_or(-1, |v| v as i32);
+        #[cfg(feature = "colored")]
+        let line = json!(line);
+        #[cfg(not(feature = "colored"))]
+        let line = json!(line.to_string());
+        #[cfg(feature = "chrono")]
+        let time = DateTime::<Local>::from(cx.time()).format("%Y-%m-%d %H:%M:%S%.3f").to_string();
+        #[cfg(not(feature = "chrono"))]
+        let time = String::new();
+
+        let mut object = json!({
+            "time" : time,
+            "level" : record.level().to_string(),
+            "message" : crate::error_trace_message(record),
+            "service" : json!({
+                "name" : name,
+                "version" : version,
+            }),
+            "location" : json!({
+                "file" : record.file().unwrap_or("<unknown>"),
+                "line" : line,
+                "target" : crate::record_target(record),
+            }),
+        });
+        let context = cx.context();
+        if !context.is_empty() {
+            let context: serde_json::Map<_, _> =
+                context.iter().map(|(key, value)| (key.clone(), value.clone().into())).collect();
+            object["context"] = context.into();
+        }
+        let fields = crate::fields::json(record);
+        if !fields.is_empty() {
+            object["fields"] = fields.into();
+        }
+        match theme {
+            Some(theme) => write_json(buf, &object, theme, 0),
+            None => serde_json::to_writer(buf, &object).map_err(io::Error::from),
+        }
+    }
+}
+
+/// `GelfFormatter` is the `gelf` layout: one GELF 1.1 message per line, for
+/// Graylog.
+///
+/// The first line of the message is the `short_message`, and the whole message
+/// also goes to `full_message` when it has several lines. The level is the syslog
+/// severity, and the file, line, target, service name, context and key-values
+/// are additional fields, such as `_target` or `_user_id`.
+#[cfg(feature = "json")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct GelfFormatter;
+
+#[cfg(feature = "json")]
+impl Formatter for GelfFormatter {
+    fn format(&self, record: &Record, cx: &FormatContext, buf: &mut Vec<u8>) -> io::Result<()> {
+        use serde_json::{json, Value};
+        use std::time::UNIX_EPOCH;
+
+        let theme = cx.theme();
+        // The location is in `_file` and `_line`, not appended to errors.
+        let message = record.args().to_string();
+        let since_epoch = cx.time().duration_since(UNIX_EPOCH).unwrap_or_default();
+        let timestamp =
+            since_epoch.as_secs() as f64 + f64::from(since_epoch.subsec_millis()) / 1000.0;
+        let level = crate::syslog_severity(record.level());
+        let service = crate::service_name();
+
+        let mut object = json!({
+            "version" : "1.1",
+            "host" : crate::syslog::hostname(),
+            "short_message" : message.lines().next().unwrap_or_default(),
+            "timestamp" : timestamp,
+            "level" : level,
+            "_file" : record.file().unwrap_or("<unknown>"),
+            "_line" : record.line().map_or(-1, |v| v as i32),
+            "_target" : crate::record_target(record),
+            "_service_name" : service,
+        });
+        if message.contains('\n') {
+            object["full_message"] = message.into();
+        }
+        let context = cx.context().iter().map(|(key, value)| (key.clone(), value.clone().into()));
+        for (key, value) in context.chain(crate::fields::json(record)) {
+            // Additional fields are strings or numbers named after `[\w.-]`, but
+            // not `_id`.
+            let mut name: String =
+                key.chars()
+                    .map(|c| {
+                        if c.is_alphanumeric() || c == '.' || c == '-' || c == '_' {
+                            c
+                        } else {
+                            '_'
+                        }
+                    })
+                    .collect();
+            if name == "id" {
+                name.push('_');
+            }
+            let value = match value {
+                Value::String(_) | Value::Number(_) => value,
+                value => value.to_string().into(),
+            };
+            object[format!("_{}", name)] = value;
+        }
+        match theme {
+            Some(theme) => write_json(buf, &object, theme, 0),
+            None => serde_json::to_writer(buf, &object).map_err(io::Error::from),
+        }
+    }
+}
+
+/// `EcsFormatter` is the `ecs` layout: one object per line following the Elastic
+/// Common Schema, so that Kibana shows records without any mapping.
+///
+/// Records have the `@timestamp` in RFC 3339 UTC, the `log.level`, `log.logger`
+/// and `log.origin.file` (when known) of the record, the `service.name` and
+/// `service.version` (`SERVICE_NAME` and `SERVICE_VERSION`, or the ones of the
+/// crate), the `process.pid` and `process.thread.name`, and the `ecs.version`. The
+/// context and key-values are top-level fields, such as `user.id`, unless they
+/// would replace one of these.
+#[cfg(feature = "json")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct EcsFormatter;
+
+#[cfg(feature = "json")]
+impl Formatter for EcsFormatter {
+    fn format(&self, record: &Record, cx: &FormatContext, buf: &mut Vec<u8>) -> io::Result<()> {
+        use serde_json::json;
+
+        let theme = cx.theme();
+        let name = crate::service_name();
+        let version = crate::service_version();
+        let thread = std::thread::current();
+
+        let mut object = json!({
+            "@timestamp" : crate::utc::Utc::new(cx.time()).to_string(),
+            "log" : json!({
+                "level" : record.level().as_str().to_ascii_lowercase(),
+                "logger" : crate::record_target(record),
+            }),
+            // The location is in `log.origin`, not appended to errors.
+            "message" : record.args().to_string(),
+            "service" : json!({
+                "name" : name,
+                "version" : version,
+            }),
+            "process" : json!({
+                "pid" : std::process::id(),
+                "thread" : json!({
+                    "name" : thread.name().unwrap_or("<unnamed>"),
+                }),
+            }),
+            "ecs" : json!({
+                "version" : "8.11.0",
+            }),
+        });
+        // An unknown location is left out rather than given a placeholder.
+        let mut file = serde_json::Map::new();
+        if let Some(name) = record.file() {
+            file.insert("name".to_owned(), name.into());
+        }
+        if let Some(line) = record.line() {
+            file.insert("line".to_owned(), line.into());
+        }
+        if !file.is_empty() {
+            object["log"]["origin"] = json!({ "file" : file });
+        }
+        if let serde_json::Value::Object(ref mut map) = object {
+            let context =
+                cx.context().iter().map(|(key, value)| (key.clone(), value.clone().into()));
+            for (key, value) in context.chain(crate::fields::json(record)) {
+                map.entry(key).or_insert(value);
+            }
+        }
+        match theme {
+            Some(theme) => write_json(buf, &object, theme, 0),
+            None => serde_json::to_writer(buf, &object).map_err(io::Error::from),
+        }
+    }
+}
+
+/// `LogfmtFormatter` is the `logfmt` layout: `key=value` pairs with the time,
+/// level, target, file, line and message, followed by the context and key-values
+/// of the record, quoted where needed.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LogfmtFormatter;
+
+impl Formatter for LogfmtFormatter {
+    fn format(&self, record: &Record, cx: &FormatContext, buf: &mut Vec<u8>) -> io::Result<()> {
+        let theme = cx.theme();
+        let level = record.level().as_str().to_ascii_lowercase();
+        let mut pairs = Vec::new();
+        #[cfg(feature = "chrono")]
+        pairs.push((
+            "time".to_owned(),
+            DateTime::<Local>::from(cx.time()).format("%Y-%m-%dT%H:%M:%S%.3f%:z").to_string(),
+        ));
+        let level_index = pairs.len();
+        pairs.push(("level".to_owned(), level));
+        pairs.push(("target".to_owned(), crate::record_target(record).to_owned()));
+        if let Some(file) = record.file() {
+            pairs.push(("file".to_owned(), file.to_owned()));
+        }
+        if let Some(line) = record.line() {
+            pairs.push(("line".to_owned(), line.to_string()));
+        }
+        pairs.push(("msg".to_owned(), record.args().to_string()));
+        pairs.extend(cx.context().iter().cloned());
+        pairs.extend(crate::fields::pairs(record));
+
+        let mut pair = String::new();
+        for (i, (key, value)) in pairs.iter().enumerate() {
+            if i > 0 {
+                buf.push(b' ');
+            }
+            pair.clear();
+            crate::fields::push_logfmt(&mut pair, key, value);
+            match theme {
+                Some(theme) => {
+                    let (key, value) = pair.split_once('=').unwrap_or_default();
+                    let sgr =
+                        if i == level_index { theme.level_style(record.level()).sgr() } else { "" };
+                    write!(buf, "{}={}", theme.key_style().paint(key), Painted::new(sgr, value))?;
+                }
+                None => buf.extend_from_slice(pair.as_bytes()),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Formatter for crate::Template {
+    fn format(&self, record: &Record, cx: &FormatContext, buf: &mut Vec<u8>) -> io::Result<()> {
+        self.render(record, cx, buf)
+    }
+}
+
+// `value` in the style `part` of the theme, if any.
+fn paint<'a, T: std::fmt::Display>(
+    theme: Option<&'a Theme>,
+    part: fn(&Theme) -> &Style,
+    value: T,
+) -> Painted<'a, T> {
+    Painted::new(theme.map_or("", |theme| part(theme).sgr()), value)
+}
+
+// The timestamp and its separator, when the time is recorded.
+fn write_time(buf: &mut Vec<u8>, time: SystemTime, theme: Option<&Theme>) -> io::Result<()> {
+    #[cfg(feature = "chrono")]
+    {
+        let time = DateTime::<Local>::from(time).format("%Y-%m-%d %H:%M:%S%.3f");
+        write!(buf, "{} ", paint(theme, Theme::time_style, time))?;
+    }
+    let _ = (buf, time, theme);
+    Ok(())
+}
+
+// The padded level, in brackets when the time is recorded.
+fn write_level(buf: &mut Vec<u8>, level: Level, theme: Option<&Theme>) -> io::Result<()> {
+    let sgr = theme.map_or("", |theme| theme.level_style(level).sgr());
+    let level = Painted::new(sgr, level);
+    if cfg!(feature = "chrono") {
+        write!(buf, "[{:<5}] ", level)
+    } else {
+        write!(buf, "{:<5} ", level)
+    }
+}
+
+// Pretty-print `value` with colored keys, strings and numbers.
+#[cfg(feature = "json")]
+fn write_json(
+    buf: &mut Vec<u8>,
+    value: &serde_json::Value,
+    theme: &Theme,
+    indent: usize,
+) -> io::Result<()> {
+    use serde_json::Value;
+
+    let string = |s: &str| serde_json::to_string(s).unwrap_or_default();
+    let newline = |buf: &mut Vec<u8>, indent: usize| write!(buf, "\n{:1$}", "", indent * 2);
+    match value {
+        Value::String(s) => write!(buf, "{}", theme.string_style().paint(string(s))),
+        Value::Number(n) => write!(buf, "{}", theme.number_style().paint(n)),
+        Value::Array(values) if !values.is_empty() => {
+            buf.push(b'[');
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    buf.push(b',');
+                }
+                newline(buf, indent + 1)?;
+                write_json(buf, value, theme, indent + 1)?;
+            }
+            newline(buf, indent)?;
+            buf.push(b']');
+            Ok(())
+        }
+        Value::Object(map) if !map.is_empty() => {
+            buf.push(b'{');
+            for (i, (key, value)) in map.iter().enumerate() {
+                if i > 0 {
+                    buf.push(b',');
+                }
+                newline(buf, indent + 1)?;
+                write!(buf, "{}: ", theme.key_style().paint(string(key)))?;
+                write_json(buf, value, theme, indent + 1)?;
+            }
+            newline(buf, indent)?;
+            buf.push(b'}');
+            Ok(())
+        }
+        _ => serde_json::to_writer(buf, value).map_err(io::Error::from),
+    }
+}
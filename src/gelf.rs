@@ -0,0 +1,193 @@
+use crate::sink::Sink;
+use crate::tcp::TcpConnection;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::io;
+#[cfg(feature = "gzip")]
+use std::io::Write;
+use std::net::{ToSocketAddrs, UdpSocket};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// The most chunks a GELF message may be split into.
+const MAX_CHUNKS: usize = 128;
+// The magic bytes, message id, sequence number and sequence count of a chunk.
+const CHUNK_HEADER: usize = 12;
+
+enum Transport {
+    Udp(String),
+    Tcp(String),
+}
+
+enum Connection {
+    Udp(UdpSocket),
+    Tcp(TcpConnection),
+}
+
+/// `GelfSink` sends GELF messages to Graylog, over UDP or TCP.
+///
+/// Use it with the [`Gelf`](enum.LogeFormat.html#variant.Gelf) format. Over UDP,
+/// messages larger than a datagram are split into at most 128 chunks, and can be
+/// compressed with gzip (needs the `gzip` feature). Over TCP, messages are
+/// delimited by a null byte. The connection is opened on the first record, and
+/// opened again after an error. Over TCP, the connection is retried as by
+/// [`SyslogSink`](struct.SyslogSink.html), so an unreachable Graylog does not stall
+/// the threads that log.
+///
+/// ## Example
+///
+/// ```no_run
+/// # extern crate loge;
+/// loge::Builder::new()
+///     .output(
+///         loge::Output::sink(loge::GelfSink::udp("graylog.example.com:12201"))
+///             .format(loge::LogeFormat::Gelf),
+///     )
+///     .init();
+/// ```
+pub struct GelfSink {
+    transport: Transport,
+    connection: Option<Connection>,
+    chunk_size: usize,
+    #[cfg(feature = "gzip")]
+    gzip: bool,
+}
+
+impl GelfSink {
+    /// Send messages over UDP to `address`, e.g. `graylog.example.com:12201`.
+    pub fn udp<A: Into<String>>(address: A) -> GelfSink {
+        GelfSink::new(Transport::Udp(address.into()))
+    }
+
+    /// Send messages over TCP to `address`, e.g. `graylog.example.com:12201`.
+    pub fn tcp<A: Into<String>>(address: A) -> GelfSink {
+        GelfSink::new(Transport::Tcp(address.into()))
+    }
+
+    fn new(transport: Transport) -> GelfSink {
+        GelfSink {
+            transport,
+            connection: None,
+            chunk_size: 8192,
+            #[cfg(feature = "gzip")]
+            gzip: false,
+        }
+    }
+
+    /// Set the largest datagram sent over UDP, chunk headers included, 8192 bytes
+    /// by default. Lower it to 1420 when Graylog is reached over the internet.
+    pub fn chunk_size(mut self, bytes: usize) -> GelfSink {
+        self.chunk_size = bytes.max(CHUNK_HEADER + 1);
+        self
+    }
+
+    /// Compress messages sent over UDP with gzip (needs the `gzip` feature).
+    #[cfg(feature = "gzip")]
+    pub fn gzip(mut self) -> GelfSink {
+        self.gzip = true;
+        self
+    }
+
+    fn connect(&self) -> io::Result<Connection> {
+        match self.transport {
+            Transport::Udp(ref address) => {
+                let target = address.to_socket_addrs()?.next().ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::NotFound, format!("cannot resolve {}", address))
+                })?;
+                let local = if target.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+                let socket = UdpSocket::bind(local)?;
+                socket.connect(target)?;
+                Ok(Connection::Udp(socket))
+            }
+            Transport::Tcp(ref address) => Ok(Connection::Tcp(TcpConnection::new(address.clone()))),
+        }
+    }
+
+    fn send(&mut self, message: &[u8]) -> io::Result<()> {
+        if self.connection.is_none() {
+            self.connection = Some(self.connect()?);
+        }
+        let result = match self.connection {
+            Some(Connection::Udp(ref socket)) => {
+                #[cfg(feature = "gzip")]
+                let compressed;
+                #[cfg(feature = "gzip")]
+                let message = if self.gzip {
+                    let mut encoder =
+                        flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                    encoder.write_all(message)?;
+                    compressed = encoder.finish()?;
+                    &compressed[..]
+                } else {
+                    message
+                };
+                send_chunked(socket, message, self.chunk_size)
+            }
+            Some(Connection::Tcp(ref mut connection)) => {
+                let mut framed = Vec::with_capacity(message.len() + 1);
+                framed.extend_from_slice(message);
+                framed.push(0);
+                connection.write_all(&framed)
+            }
+            None => Ok(()),
+        };
+        // A TCP connection waits before connecting again on its own.
+        if result.is_err() && !matches!(self.connection, Some(Connection::Tcp(_))) {
+            self.connection = None;
+        }
+        result
+    }
+}
+
+impl Sink for GelfSink {
+    fn write(&mut self, line: &str) -> io::Result<()> {
+        self.send(line.as_bytes())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.connection {
+            Some(Connection::Tcp(ref mut connection)) => connection.flush(),
+            _ => Ok(()),
+        }
+    }
+
+    fn reopen(&mut self) -> io::Result<()> {
+        self.connection = None;
+        Ok(())
+    }
+}
+
+// Send `message` in one datagram, or split into chunks of at most `chunk_size`
+// bytes sharing a message id.
+fn send_chunked(socket: &UdpSocket, message: &[u8], chunk_size: usize) -> io::Result<()> {
+    if message.len() <= chunk_size {
+        return socket.send(message).map(drop);
+    }
+    let payload = chunk_size - CHUNK_HEADER;
+    let count = message.len().div_ceil(payload);
+    if count > MAX_CHUNKS {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("GELF message of {} bytes needs more than 128 chunks", message.len()),
+        ));
+    }
+    let id = message_id().to_be_bytes();
+    let mut chunk = Vec::with_capacity(chunk_size);
+    for (sequence, part) in message.chunks(payload).enumerate() {
+        chunk.clear();
+        chunk.extend_from_slice(&[0x1e, 0x0f]);
+        chunk.extend_from_slice(&id);
+        chunk.extend_from_slice(&[sequence as u8, count as u8]);
+        chunk.extend_from_slice(part);
+        socket.send(&chunk)?;
+    }
+    Ok(())
+}
+
+// A message id unlikely to be used by another message or another process.
+fn message_id() -> u64 {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+    hasher.write_u32(std::process::id());
+    hasher.finish()
+}
@@ -0,0 +1,57 @@
+use crate::handle::LogeHandle;
+use crate::{FLUSH_TIMEOUT, LOGGER};
+use std::time::Duration;
+
+/// `LogeGuard` shuts `loge` down when dropped.
+///
+/// Keep it alive in `main` so that the last records before the program exits are
+/// written: on drop it waits for the asynchronous queue to drain, stops the writer
+/// thread and flushes every sink, as [`shutdown`](fn.shutdown.html) does.
+///
+/// ## Example
+///
+/// ```
+/// # extern crate loge;
+/// let _guard = loge::Builder::new().asynchronous(loge::Queue::new(1024)).init_with_guard();
+/// log::error!("written before the program exits");
+/// ```
+#[must_use = "the logger is shut down as soon as the guard is dropped"]
+#[derive(Debug)]
+pub struct LogeGuard {
+    handle: LogeHandle,
+    timeout: Duration,
+}
+
+impl LogeGuard {
+    pub(crate) fn new(handle: LogeHandle) -> LogeGuard {
+        LogeGuard { handle, timeout: FLUSH_TIMEOUT }
+    }
+
+    /// Get a handle to change the level and format of the logger at runtime.
+    pub fn handle(&self) -> LogeHandle {
+        self.handle.clone()
+    }
+
+    /// Set how long dropping the guard waits for queued records, 5 seconds by default.
+    pub fn timeout(mut self, timeout: Duration) -> LogeGuard {
+        self.timeout = timeout;
+        self
+    }
+}
+
+impl Drop for LogeGuard {
+    fn drop(&mut self) {
+        shutdown(self.timeout);
+    }
+}
+
+/// Write every queued record, stop the writer thread and flush every sink.
+///
+/// Waits at most `timeout` for the queue to drain and returns whether it did.
+/// Records logged afterwards are still written, directly on the logging thread.
+pub fn shutdown(timeout: Duration) -> bool {
+    match LOGGER.get() {
+        Some(logger) => logger.sinks.shutdown(timeout),
+        None => true,
+    }
+}
@@ -0,0 +1,81 @@
+use crate::filter::Filter;
+use crate::{LogeFormat, Settings};
+use log::LevelFilter;
+use std::fmt;
+use std::sync::{Arc, RwLock, RwLockWriteGuard};
+
+/// `LogeHandle` changes the level filter and format of the installed logger at
+/// runtime, without reinitializing it.
+///
+/// Each change applies atomically to the records logged afterwards, and keeps
+/// `log::max_level()` in sync so disabled records stay cheap. Handles are cheap to
+/// clone and can be shared between threads.
+///
+/// ## Example
+///
+/// ```
+/// # extern crate loge;
+/// let handle = loge::Builder::new().level(log::Level::Info).init();
+/// // Turn on debug logs for one module, e.g. from an admin endpoint.
+/// handle.set_module_level("my_crate::db", log::LevelFilter::Debug);
+/// handle.set_format(loge::LogeFormat::Fileline);
+/// ```
+#[derive(Clone)]
+pub struct LogeHandle {
+    settings: Arc<RwLock<Settings>>,
+}
+
+impl LogeHandle {
+    pub(crate) fn new(settings: Arc<RwLock<Settings>>) -> LogeHandle {
+        LogeHandle { settings }
+    }
+
+    fn settings(&self) -> RwLockWriteGuard<'_, Settings> {
+        self.settings.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    // Change the filter with `f`, then publish the new maximum level.
+    fn update_filter<F: FnOnce(&mut Filter)>(&self, f: F) {
+        let mut settings = self.settings();
+        f(&mut settings.filter);
+        log::set_max_level(settings.max_level());
+    }
+
+    /// Set the level of targets without a more specific module directive.
+    pub fn set_level(&self, level: LevelFilter) {
+        self.update_filter(|filter| filter.insert(None, level))
+    }
+
+    /// Set the level of `module` and the modules below it.
+    pub fn set_module_level(&self, module: &str, level: LevelFilter) {
+        self.update_filter(|filter| filter.insert(Some(module), level))
+    }
+
+    /// Replace every level directive and the message filter with `spec`, in the
+    /// `RUST_LOG` syntax, e.g. `info,my_crate=debug/timeout`. Targets the spec does
+    /// not cover log `Level::Error` and above.
+    pub fn set_filters(&self, spec: &str) {
+        self.update_filter(|filter| {
+            let mut replacement = Filter::new(LevelFilter::Error);
+            replacement.extend(spec);
+            *filter = replacement;
+        })
+    }
+
+    // Replace the whole filter, e.g. with one rebuilt from a reloaded configuration.
+    #[cfg(feature = "config")]
+    pub(crate) fn set_filter(&self, replacement: Filter) {
+        self.update_filter(|filter| *filter = replacement)
+    }
+
+    /// Set the format of the records logged afterwards.
+    pub fn set_format(&self, format: LogeFormat) {
+        self.settings().format = format;
+    }
+}
+
+impl fmt::Debug for LogeHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("LogeHandle").finish()
+    }
+}
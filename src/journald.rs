@@ -0,0 +1,204 @@
+use crate::sink::{RecordData, Sink};
+use std::fs::File;
+use std::io::{self, Write};
+use std::os::unix::io::{AsRawFd, FromRawFd};
+use std::os::unix::net::UnixDatagram;
+use std::path::{Path, PathBuf};
+
+/// `JournaldSink` sends records to the systemd journal over its native protocol.
+///
+/// Besides the formatted line as `MESSAGE`, every entry has the `PRIORITY`,
+/// `CODE_FILE`, `CODE_LINE` and `TARGET` of the record, and its context and
+/// key-values as fields named in upper case, e.g. `REQUEST_ID`, so they can be
+/// matched with `journalctl REQUEST_ID=7f3a`. Records too large for a datagram
+/// are passed in a sealed memory file, as `sd_journal_send` does.
+///
+/// ## Example
+///
+/// ```no_run
+/// # extern crate loge;
+/// loge::Builder::new()
+///     .console(false)
+///     .output(
+///         loge::Output::sink(loge::JournaldSink::new())
+///             .format(loge::LogeFormat::Template("{message}".parse().unwrap())),
+///     )
+///     .init();
+/// ```
+pub struct JournaldSink {
+    path: PathBuf,
+    socket: Option<UnixDatagram>,
+    identifier: String,
+    // The encoded entry, reused from one record to the next.
+    buf: Vec<u8>,
+}
+
+impl Default for JournaldSink {
+    fn default() -> Self {
+        JournaldSink::new()
+    }
+}
+
+impl JournaldSink {
+    /// Send records to the journal of the system, at `/run/systemd/journal/socket`.
+    pub fn new() -> JournaldSink {
+        JournaldSink::socket("/run/systemd/journal/socket")
+    }
+
+    /// Send records to the journal listening on the Unix datagram socket at `path`.
+    pub fn socket<P: Into<PathBuf>>(path: P) -> JournaldSink {
+        JournaldSink {
+            path: path.into(),
+            socket: None,
+            identifier: crate::service_name(),
+            buf: Vec::new(),
+        }
+    }
+
+    /// Set the `SYSLOG_IDENTIFIER` of the entries, `SERVICE_NAME` or
+    /// `CARGO_PKG_NAME` by default.
+    pub fn syslog_identifier<S: Into<String>>(mut self, identifier: S) -> JournaldSink {
+        self.identifier = identifier.into();
+        self
+    }
+
+    fn send(&mut self) -> io::Result<()> {
+        if self.socket.is_none() {
+            self.socket = Some(UnixDatagram::unbound()?);
+        }
+        let socket = self.socket.as_ref().expect("the socket was just opened");
+        match socket.send_to(&self.buf, &self.path) {
+            Ok(_) => Ok(()),
+            Err(ref e)
+                if e.raw_os_error() == Some(libc::EMSGSIZE)
+                    || e.raw_os_error() == Some(libc::ENOBUFS) =>
+            {
+                send_memfd(socket, &self.path, &self.buf)
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl Sink for JournaldSink {
+    fn write(&mut self, line: &str) -> io::Result<()> {
+        self.buf.clear();
+        push_field(&mut self.buf, "MESSAGE", line);
+        push_field(&mut self.buf, "PRIORITY", "6");
+        if !self.identifier.is_empty() {
+            push_field(&mut self.buf, "SYSLOG_IDENTIFIER", &self.identifier);
+        }
+        self.send()
+    }
+
+    fn structured(&self) -> bool {
+        true
+    }
+
+    fn write_record(&mut self, line: &str, record: &RecordData) -> io::Result<()> {
+        let priority = crate::syslog_severity(record.level()).to_string();
+        let buf = &mut self.buf;
+        buf.clear();
+        push_field(buf, "MESSAGE", line);
+        push_field(buf, "PRIORITY", &priority);
+        if !self.identifier.is_empty() {
+            push_field(buf, "SYSLOG_IDENTIFIER", &self.identifier);
+        }
+        if let Some(file) = record.file() {
+            push_field(buf, "CODE_FILE", file);
+        }
+        if let Some(line) = record.line() {
+            push_field(buf, "CODE_LINE", &line.to_string());
+        }
+        push_field(buf, "TARGET", record.target());
+        for (key, value) in record.context().iter().chain(record.fields()) {
+            let name = field_name(key);
+            if !name.is_empty() {
+                push_field(buf, &name, value);
+            }
+        }
+        self.send()
+    }
+}
+
+// Encode a field: `NAME=value\n`, or, when the value has a newline, the name, a
+// newline, the length of the value as a little-endian u64, the value and a newline.
+fn push_field(buf: &mut Vec<u8>, name: &str, value: &str) {
+    buf.extend_from_slice(name.as_bytes());
+    if value.contains('\n') {
+        buf.push(b'\n');
+        buf.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    } else {
+        buf.push(b'=');
+    }
+    buf.extend_from_slice(value.as_bytes());
+    buf.push(b'\n');
+}
+
+// A journal field name for `key`: upper-case letters, digits and underscores, not
+// starting with an underscore or a digit, at most 64 characters.
+fn field_name(key: &str) -> String {
+    key.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .skip_while(|&c| c == '_' || c.is_ascii_digit())
+        .take(64)
+        .collect()
+}
+
+// Pass `entry` in a sealed memory file, for entries larger than a datagram.
+fn send_memfd(socket: &UnixDatagram, path: &Path, entry: &[u8]) -> io::Result<()> {
+    use std::os::unix::ffi::OsStrExt;
+
+    // SAFETY: the name is NUL-terminated, and the returned descriptor is owned by
+    // `file` from now on.
+    let file = unsafe {
+        let fd = libc::memfd_create(
+            b"loge-journal\0".as_ptr() as *const libc::c_char,
+            libc::MFD_ALLOW_SEALING | libc::MFD_CLOEXEC,
+        );
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        File::from_raw_fd(fd)
+    };
+    (&file).write_all(entry)?;
+    let seals = libc::F_SEAL_SHRINK | libc::F_SEAL_GROW | libc::F_SEAL_WRITE | libc::F_SEAL_SEAL;
+    // SAFETY: `file` is a valid descriptor, and `F_ADD_SEALS` takes an int.
+    if unsafe { libc::fcntl(file.as_raw_fd(), libc::F_ADD_SEALS, seals) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let path = path.as_os_str().as_bytes();
+    // SAFETY: every structure is zeroed before use, the path fits in `sun_path`
+    // (checked below), and the control buffer is large and aligned enough for
+    // one `SCM_RIGHTS` message holding one descriptor.
+    unsafe {
+        let mut address: libc::sockaddr_un = std::mem::zeroed();
+        if path.len() >= address.sun_path.len() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "socket path is too long"));
+        }
+        address.sun_family = libc::AF_UNIX as libc::sa_family_t;
+        for (dst, &src) in address.sun_path.iter_mut().zip(path) {
+            *dst = src as libc::c_char;
+        }
+        let address_len = std::mem::size_of::<libc::sa_family_t>() + path.len() + 1;
+
+        let fd = file.as_raw_fd();
+        let mut control = [0u64; 4];
+        let control_len = libc::CMSG_SPACE(std::mem::size_of_val(&fd) as u32) as usize;
+        let mut message: libc::msghdr = std::mem::zeroed();
+        message.msg_name = &mut address as *mut libc::sockaddr_un as *mut libc::c_void;
+        message.msg_namelen = address_len as libc::socklen_t;
+        message.msg_control = control.as_mut_ptr() as *mut libc::c_void;
+        message.msg_controllen = control_len as _;
+        let header = libc::CMSG_FIRSTHDR(&message);
+        (*header).cmsg_level = libc::SOL_SOCKET;
+        (*header).cmsg_type = libc::SCM_RIGHTS;
+        (*header).cmsg_len = libc::CMSG_LEN(std::mem::size_of_val(&fd) as u32) as _;
+        std::ptr::write_unaligned(libc::CMSG_DATA(header) as *mut libc::c_int, fd);
+        if libc::sendmsg(socket.as_raw_fd(), &message, 0) < 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
@@ -27,63 +27,303 @@
 extern crate atty;
 #[cfg(feature = "chrono")]
 extern crate chrono;
-#[cfg(feature = "colored")]
-extern crate colored;
-#[cfg(feature = "json")]
-extern crate json_color;
+#[cfg(feature = "gzip")]
+extern crate flate2;
+#[cfg(unix)]
+extern crate libc;
 extern crate log;
+#[cfg(feature = "regex")]
+extern crate regex;
 #[cfg(feature = "json")]
 extern crate serde_json;
 #[cfg(windows)]
 extern crate winapi;
+#[cfg(feature = "zstd")]
+extern crate zstd;
 
-#[cfg(feature = "chrono")]
-use chrono::Local;
-#[cfg(feature = "colored")]
-use colored::{ColoredString, Colorize};
-use log::{Level, Log, Metadata, Record, SetLoggerError};
-#[cfg(feature = "file")]
-mod filewriter;
-#[cfg(feature = "file")]
+use log::{Level, LevelFilter, Log, Metadata, Record, SetLoggerError};
+use std::cell::RefCell;
+use std::env;
+use std::io;
 use std::path::Path;
-#[cfg(feature = "file")]
-use std::sync::{Arc, RwLock};
-
-#[cfg(feature = "file")]
+use std::sync::{Arc, OnceLock, RwLock, RwLockReadGuard};
+use std::time::{Duration, SystemTime};
+
+mod asyncwriter;
+mod builder;
+mod color;
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+mod compressor;
+#[cfg(feature = "config")]
+mod config;
+pub mod context;
+mod error;
+mod fields;
+mod filewriter;
+mod filter;
+mod formatter;
+mod gelf;
+mod guard;
+mod handle;
+#[cfg(target_os = "linux")]
+mod journald;
+mod output;
+mod panichook;
+mod reopen;
+mod sink;
+mod syslog;
+mod tcp;
+mod template;
+mod theme;
+mod utc;
+
+pub use asyncwriter::{Overflow, Queue};
+pub use builder::Builder;
+pub use color::ColorChoice;
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+pub use compressor::Compression;
+pub use error::{FailedWrites, Fallback, OnError};
+#[cfg(feature = "chrono")]
+pub use filewriter::Period;
+pub use filewriter::{Naming, Rotation};
+#[cfg(feature = "json")]
+pub use formatter::{EcsFormatter, GelfFormatter, JsonFormatter};
+pub use formatter::{
+    FilelineFormatter, FormatContext, Formatter, LogfmtFormatter, TargetFormatter,
+};
+pub use gelf::GelfSink;
+pub use guard::{shutdown, LogeGuard};
+pub use handle::LogeHandle;
+#[cfg(target_os = "linux")]
+pub use journald::JournaldSink;
+pub use output::{Output, Stream};
+pub use panichook::install_panic_hook;
+pub use reopen::reopen;
+#[cfg(unix)]
+pub use reopen::reopen_on_sighup;
+pub use sink::{FileSink, RecordData, Sink, StderrSink, StdoutSink};
+pub use syslog::{Facility, SyslogProtocol, SyslogSink};
+pub use template::{Template, TemplateError};
+pub use theme::{Color, Style, Theme};
+
+#[derive(Clone)]
 struct LogeLogger {
-    level: Level,
-    config: LogeFormat,
-    writer: Arc<RwLock<filewriter::FileWriter>>,
+    settings: Arc<RwLock<Settings>>,
+    sinks: Arc<Sinks>,
 }
-#[cfg(feature = "file")]
-impl LogeLogger {
-    /// Create a new logger.
-    pub fn new<P: AsRef<Path>>(
-        log_level: log::Level,
-        log_config: LogeFormat,
-        log_file: P,
-    ) -> LogeLogger {
-        LogeLogger {
-            level: log_level,
-            config: log_config,
-            writer: Arc::new(RwLock::new(filewriter::FileWriter::new(
-                log_file.as_ref().to_path_buf(),
-            ))),
+
+// What the logger records and how, swapped at runtime through a `LogeHandle`.
+struct Settings {
+    filter: filter::Filter,
+    format: LogeFormat,
+    theme: Theme,
+    // The most verbose level of the outputs with their own filter.
+    outputs_level: LevelFilter,
+}
+
+impl Settings {
+    fn max_level(&self) -> LevelFilter {
+        self.filter.max_level().max(self.outputs_level)
+    }
+}
+
+// How long flushing waits for queued records before giving up.
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(5);
+
+// The installed logger, for `shutdown`, `LogeGuard` and the records of `loge` itself.
+static LOGGER: OnceLock<LogeLogger> = OnceLock::new();
+
+// Write a record of `loge` itself, such as a report of dropped records, to every
+// output regardless of its filter.
+fn log_unfiltered(record: &Record) {
+    if let Some(logger) = LOGGER.get() {
+        logger.dispatch(record, false);
+    }
+}
+
+// Where rendered records are written.
+struct Sinks {
+    outputs: Arc<Outputs>,
+    queue: Option<asyncwriter::AsyncWriter>,
+}
+
+impl Sinks {
+    /// Create sinks writing to `channels`, optionally from a background thread.
+    /// The first channel holds the file set with `Builder::file`, and is inactive
+    /// while there is none.
+    fn new(channels: Vec<output::Channel>, log_queue: Option<Queue>, on_error: OnError) -> Sinks {
+        let outputs = Arc::new(Outputs { channels, errors: error::ErrorHandler::new(on_error) });
+        let queue = log_queue.map(|config| {
+            let outputs = outputs.clone();
+            asyncwriter::AsyncWriter::new(config, move |entry| outputs.emit(entry))
+        });
+        Sinks { outputs, queue }
+    }
+
+    fn write(&self, entry: asyncwriter::Entry) {
+        let entry = match self.queue {
+            Some(ref queue) if !asyncwriter::on_writer_thread() => queue.push(entry),
+            _ => Some(entry),
+        };
+        if let Some(entry) = entry {
+            self.outputs.emit(entry)
         }
     }
+
+    // Wait for queued records for at most `timeout`, then flush every output.
+    fn flush(&self, timeout: Duration) -> bool {
+        // The writer thread would wait for itself, e.g. when it panics.
+        let written = asyncwriter::on_writer_thread()
+            || self.queue.as_ref().is_none_or(|queue| queue.flush(timeout));
+        self.outputs.flush();
+        written
+    }
+
+    // Replace the log file, dropping the previous one outside the lock.
+    #[cfg(feature = "config")]
+    fn set_file(&self, file: Option<FileSink>) {
+        let active = file.is_some();
+        let sink: Box<dyn Sink> = match file {
+            Some(file) => Box::new(file),
+            None => Box::new(sink::NoSink),
+        };
+        let channel = &self.outputs.channels[0];
+        let previous = std::mem::replace(&mut *channel.sink(), sink);
+        channel.set_active(active);
+        drop(previous);
+    }
+
+    // Reopen every file, returning the first error.
+    fn reopen(&self) -> io::Result<()> {
+        self.outputs.channels.iter().try_for_each(|channel| channel.sink().reopen())
+    }
+
+    // Like `flush`, but also stop the writer thread.
+    fn shutdown(&self, timeout: Duration) -> bool {
+        let written = self.queue.as_ref().is_none_or(|queue| queue.stop(timeout));
+        self.flush(Duration::from_secs(0)) && written
+    }
 }
-#[cfg(not(feature = "file"))]
-struct LogeLogger {
-    level: Level,
-    config: LogeFormat,
+
+// The outputs, shared with the writer thread.
+struct Outputs {
+    channels: Vec<output::Channel>,
+    errors: error::ErrorHandler,
 }
-#[cfg(not(feature = "file"))]
+
+impl Outputs {
+    // Write rendered lines, each to the channel at its index.
+    fn emit(&self, entry: asyncwriter::Entry) {
+        for (index, line) in entry.lines {
+            let channel = &self.channels[index];
+            let mut sink = channel.sink();
+            let result = match entry.record {
+                Some(ref record) if channel.structured => sink.write_record(&line, record),
+                _ => sink.write(&line),
+            };
+            if let Err(e) = result {
+                self.errors.failed(channel.destination, &e, Some(&line));
+            }
+        }
+    }
+
+    fn flush(&self) {
+        for channel in &self.channels {
+            if let Err(e) = channel.sink().flush() {
+                self.errors.failed(channel.destination, &e, None);
+            }
+        }
+    }
+}
+
+/// Get how many records could not be written since the logger was installed.
+pub fn failed_writes() -> FailedWrites {
+    LOGGER.get().map(|logger| logger.sinks.outputs.errors.counts()).unwrap_or_default()
+}
+
+/// Get how many records the [`Queue`](struct.Queue.html) of asynchronous logging
+/// dropped because it was full, since the logger was installed.
+pub fn dropped_records() -> u64 {
+    LOGGER.get().and_then(|logger| logger.sinks.queue.as_ref()).map_or(0, |queue| queue.dropped())
+}
+
 impl LogeLogger {
-    /// Create a new logger.
-    pub fn new(log_level: log::Level, log_config: LogeFormat) -> LogeLogger {
-        LogeLogger { level: log_level, config: log_config }
+    /// Create a new logger writing into `sinks`, with settings shared with a handle.
+    fn new(settings: Arc<RwLock<Settings>>, sinks: Arc<Sinks>) -> LogeLogger {
+        LogeLogger { settings, sinks }
+    }
+
+    fn settings(&self) -> RwLockReadGuard<'_, Settings> {
+        self.settings.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    // Render `record` with `format` into the thread's buffer.
+    fn render(format: &LogeFormat, record: &Record, cx: &FormatContext) -> io::Result<Arc<str>> {
+        thread_local! {
+            static BUFFER: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+        }
+        let render = |buf: &mut Vec<u8>| {
+            buf.clear();
+            format.formatter().format(record, cx, buf)?;
+            Ok(Arc::from(String::from_utf8_lossy(buf)))
+        };
+        // A formatter logging on its own falls back to a fresh buffer.
+        BUFFER.with(|buffer| match buffer.try_borrow_mut() {
+            Ok(mut buffer) => render(&mut buffer),
+            Err(_) => render(&mut Vec::new()),
+        })
+    }
+
+    // Render `record` for every output it is routed to, and write it. `loge`'s own
+    // records skip the filters, which would otherwise hide them.
+    fn dispatch(&self, record: &Record, filtered: bool) {
+        let settings = self.settings();
+        // One time and context for every output, and for sinks wanting the record.
+        let time = SystemTime::now();
+        let context = context::fields();
+        // Lines in the logger's format, rendered once with and without colors for
+        // every output using it.
+        let mut shared = [None, None];
+        let mut lines = Vec::new();
+        let mut structured = false;
+        for (index, channel) in self.sinks.outputs.channels.iter().enumerate() {
+            if !channel.is_active()
+                || !channel.levels.contains(&record.level())
+                || filtered && !channel.filter.as_ref().unwrap_or(&settings.filter).matches(record)
+            {
+                continue;
+            }
+            let theme = if channel.color { Some(&settings.theme) } else { None };
+            let cx = FormatContext::new(time, &context, theme);
+            let rendered = match channel.format {
+                Some(ref format) => LogeLogger::render(format, record, &cx),
+                None => shared[channel.color as usize]
+                    .get_or_insert_with(|| LogeLogger::render(&settings.format, record, &cx))
+                    .as_ref()
+                    .map(Arc::clone)
+                    .map_err(|e| io::Error::new(e.kind(), e.to_string())),
+            };
+            match rendered {
+                Ok(line) => {
+                    structured |= channel.structured;
+                    lines.push((index, line));
+                }
+                Err(e) => self.sinks.outputs.errors.failed(channel.destination, &e, None),
+            }
+        }
+        drop(settings);
+        if !lines.is_empty() {
+            let record_data =
+                if structured { Some(RecordData::new(record, time, context)) } else { None };
+            self.sinks.write(asyncwriter::Entry {
+                level: record.level(),
+                lines,
+                record: record_data,
+            })
+        }
     }
 }
+
 /// `LogeFormat` is used to choose log format.
 pub enum LogeFormat {
     /// log format with `record.target()`
@@ -93,267 +333,54 @@ pub enum LogeFormat {
     /// json log format
     #[cfg(feature = "json")]
     Json,
+    /// logfmt log format, `key=value` pairs
+    Logfmt,
+    /// GELF 1.1 log format, for Graylog
+    #[cfg(feature = "json")]
+    Gelf,
+    /// Elastic Common Schema JSON log format, for Elasticsearch and Kibana
+    #[cfg(feature = "json")]
+    Ecs,
+    /// log format laid out by a user-defined [`Template`](struct.Template.html)
+    Template(Template),
+    /// log format laid out by a user-defined [`Formatter`](trait.Formatter.html)
+    Custom(Box<dyn Formatter>),
+}
+
+impl LogeFormat {
+    fn formatter(&self) -> &dyn Formatter {
+        match self {
+            LogeFormat::Target => &TargetFormatter,
+            LogeFormat::Fileline => &FilelineFormatter,
+            #[cfg(feature = "json")]
+            LogeFormat::Json => &JsonFormatter,
+            LogeFormat::Logfmt => &LogfmtFormatter,
+            #[cfg(feature = "json")]
+            LogeFormat::Gelf => &GelfFormatter,
+            #[cfg(feature = "json")]
+            LogeFormat::Ecs => &EcsFormatter,
+            LogeFormat::Template(template) => template,
+            LogeFormat::Custom(formatter) => formatter.as_ref(),
+        }
+    }
 }
 
 impl Log for LogeLogger {
     fn enabled(&self, metadata: &Metadata) -> bool {
-        metadata.level() <= self.level
+        let settings = self.settings();
+        self.sinks.outputs.channels.iter().any(|channel| {
+            channel.is_active()
+                && channel.levels.contains(&metadata.level())
+                && channel.filter.as_ref().unwrap_or(&settings.filter).enabled(metadata)
+        })
     }
 
     fn log(&self, record: &Record) {
-        let level = record.level();
-        let target = if !record.target().is_empty() {
-            record.target()
-        } else {
-            record.module_path().unwrap_or_default()
-        };
-        let file = record.file().unwrap_or("<unknown>");
-        let line = record.line().map_or(-1, |v| v as i32);
-        let msg = error_trace_message(record);
-        #[cfg(feature = "colored")]
-        {
-            let color_level = colored_level(record.level());
-            match self.config {
-                LogeFormat::Target => {
-                    #[cfg(feature = "chrono")]
-                    {
-                        let time = Local::now().format("%Y-%m-%d %H:%M:%S%.3f").to_string();
-                        let buffer = format!(
-                            "{} [{:<5}] {} ... {}",
-                            time.bright_black(),
-                            color_level,
-                            target.bold(),
-                            msg
-                        );
-                        #[cfg(feature = "file")]
-                        {
-                            let buffer = format!("{} [{:<5}] {} ... {}", time, level, target, msg);
-                            let guard = self.writer.as_ref();
-                            let mut writer = guard.write().unwrap();
-                            writer.write(buffer).unwrap()
-                        }
-                        println!("{}", buffer)
-                    }
-                    #[cfg(not(feature = "chrono"))]
-                    {
-                        let buffer = format!("{:<5} {} ... {}", color_level, target.bold(), msg);
-                        #[cfg(feature = "file")]
-                        {
-                            let buffer = format!("{:<5} {} ... {}", level, target, msg);
-                            let guard = self.writer.as_ref();
-                            let mut writer = guard.write().unwrap();
-                            writer.write(buffer).unwrap()
-                        }
-                        println!("{}", buffer)
-                    }
-                }
-                LogeFormat::Fileline => {
-                    #[cfg(feature = "chrono")]
-                    {
-                        let time = Local::now().format("%Y-%m-%d %H:%M:%S%.3f").to_string();
-                        let buffer = format!(
-                            "{} [{:<5}] {} - {} (line {}) ... {}",
-                            time.bright_black(),
-                            color_level,
-                            file.bold(),
-                            target.bold(),
-                            line.to_string().magenta(),
-                            msg
-                        );
-                        #[cfg(feature = "file")]
-                        {
-                            let buffer = format!(
-                                "{} [{:<5}] {} - {} (line {}) ... {}",
-                                time, level, file, target, line, msg
-                            );
-                            let guard = self.writer.as_ref();
-                            let mut writer = guard.write().unwrap();
-                            writer.write(buffer).unwrap()
-                        }
-                        println!("{}", buffer)
-                    }
-                    #[cfg(not(feature = "chrono"))]
-                    {
-                        let buffer = format!(
-                            "{:<5} {} - {} (line {}) ... {}",
-                            color_level,
-                            file.bold(),
-                            target.bold(),
-                            line.to_string().magenta(),
-                            msg
-                        );
-                        #[cfg(feature = "file")]
-                        {
-                            let buffer = format!(
-                                "{:<5} {} - {} (line {}) ... {}",
-                                level, file, target, line, msg
-                            );
-                            let guard = self.writer.as_ref();
-                            let mut writer = guard.write().unwrap();
-                            writer.write(buffer.unwrap())
-                        }
-                        println!("{}", buffer)
-                    }
-                }
-                #[cfg(feature = "json")]
-                LogeFormat::Json => {
-                    use json_color::{Color, Colorizer};
-                    use serde_json::json;
-                    let colorizer = Colorizer::new()
-                        .number(Color::Magenta)
-                        .string(Color::Green)
-                        .key(Color::Cyan)
-                        .build();
-
-                    let time = Local::now().format("%Y-%m-%d %H:%M:%S%.3f").to_string();
-                    // Get crate name from env.
-                    let name = ::std::env::var("SERVICE_NAME")
-                        .or_else(|_| ::std::env::var("CARGO_PKG_NAME"))
-                        .unwrap_or_else(|_| String::new());
-                    // Get crate version from env.
-                    let version = ::std::env::var("SERVICE_VERSION")
-                        .or_else(|_| ::std::env::var("CARGO_PKG_VERSION"))
-                        .unwrap_or_else(|_| String::new());
-
-                    let buffer = json!({
-                        "time" : time,
-                        "level" : level.to_string(),
-                        "message" : msg,
-                        "service" : json!({
-                            "name" : name,
-                            "version" : version,
-                        }),
-                        "location" : json!({
-                            "file" : file,
-                            "line" : line,
-                            "target" : target,
-                        }),
-                    });
-                    #[cfg(feature = "file")]
-                    {
-                        let guard = self.writer.as_ref();
-                        let mut writer = guard.write().unwrap();
-                        writer.write(buffer.to_string()).unwrap()
-                    }
-                    if let Ok(jsonified_log) = colorizer.colorize_json_str(&buffer.to_string()) {
-                        println!("{}", jsonified_log)
-                    }
-                }
-            }
-        }
-        #[cfg(not(feature = "colored"))]
-        {
-            match self.config {
-                LogeFormat::Target => {
-                    #[cfg(feature = "chrono")]
-                    {
-                        let time = Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
-                        let buffer = format!("{} [{:<5}] {} ... {}", time, level, target, msg);
-                        #[cfg(feature = "file")]
-                        {
-                            let guard = self.writer.as_ref();
-                            let mut writer = guard.write().unwrap();
-                            writer.write(buffer.unwrap())
-                        }
-                        #[cfg(not(feature = "file"))]
-                        println!("{}", buffer)
-                    }
-                    #[cfg(not(feature = "chrono"))]
-                    {
-                        let buffer = format!("{:<5} {} ... {}", level, target, msg);
-                        #[cfg(feature = "file")]
-                        {
-                            let guard = self.writer.as_ref();
-                            let mut writer = guard.write().unwrap();
-                            writer.write(buffer.unwrap())
-                        }
-                        #[cfg(not(feature = "file"))]
-                        println!("{}", buffer)
-                    }
-                }
-                LogeFormat::Fileline => {
-                    #[cfg(feature = "chrono")]
-                    {
-                        let time = Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
-                        let buffer = format!(
-                            "{} [{:<5}] {} - {} (line {}) ... {}",
-                            time, level, file, target, line, msg
-                        );
-                        #[cfg(feature = "file")]
-                        {
-                            let guard = self.writer.as_ref();
-                            let mut writer = guard.write().unwrap();
-                            writer.write(buffer.unwrap())
-                        }
-                        #[cfg(not(feature = "file"))]
-                        println!("{}", buffer)
-                    }
-                    #[cfg(not(feature = "chrono"))]
-                    {
-                        let buffer = format!(
-                            "{:<5} {} - {} (line {}) ... {}",
-                            level, file, target, line, msg
-                        );
-                        #[cfg(feature = "file")]
-                        {
-                            let guard = self.writer.as_ref();
-                            let mut writer = guard.write().unwrap();
-                            writer.write(buffer.unwrap())
-                        }
-                        #[cfg(not(feature = "file"))]
-                        println!("{}", buffer)
-                    }
-                }
-                #[cfg(feature = "json")]
-                LogeFormat::Json => {
-                    use serde_json::json;
-
-                    let time = Local::now().format("%Y-%m-%d %H:%M:%S%.3f").to_string();
-                    // Get crate name from env.
-                    let name = ::std::env::var("SERVICE_NAME")
-                        .or_else(|_| ::std::env::var("CARGO_PKG_NAME"))
-                        .unwrap_or_else(|_| String::new());
-                    // Get crate version from env.
-                    let version = ::std::env::var("SERVICE_VERSION")
-                        .or_else(|_| ::std::env::var("CARGO_PKG_VERSION"))
-                        .unwrap_or_else(|_| String::new());
-
-                    let jsonified = json!({
-                        "time" : time,
-                        "level" : level.to_string(),
-                        "message" : msg,
-                        "service" : json!({
-                            "name" : name,
-                            "version" : version,
-                        }),
-                        "location" : json!({
-                            "file" : file.to_string(),
-                            "line" : line.to_string(),
-                            "target" : target.to_string(),
-                        }),
-                    });
-
-                    let buffer = format!("{}", jsonified);
-                    #[cfg(feature = "file")]
-                    {
-                        let guard = self.writer.as_ref();
-                        let mut writer = guard.write().unwrap();
-                        writer.write(buffer.unwrap())
-                    }
-                    #[cfg(not(feature = "file"))]
-                    println!("{}", buffer)
-                }
-            }
-        }
+        self.dispatch(record, true)
     }
 
     fn flush(&self) {
-        #[cfg(feature = "file")]
-        {
-            let guard = self.writer.as_ref();
-            let writer = guard.write().unwrap();
-            writer.flush().unwrap();
-        }
+        self.sinks.flush(FLUSH_TIMEOUT);
     }
 }
 
@@ -387,22 +414,20 @@ fn set_up_color_terminal() {
     }
 }
 
-// Provide color for `level`.
-#[cfg(feature = "colored")]
-fn colored_level(level: Level) -> ColoredString {
-    match level {
-        Level::Trace => level.to_string().cyan(),
-        Level::Debug => level.to_string().blue(),
-        Level::Info => level.to_string().green(),
-        Level::Warn => level.to_string().yellow(),
-        Level::Error => level.to_string().red(),
+// The target of `record`, falling back to its module path.
+fn record_target<'a>(record: &'a Record) -> &'a str {
+    if !record.target().is_empty() {
+        record.target()
+    } else {
+        record.module_path().unwrap_or_default()
     }
 }
 
-// Error messages also have a pseudo stack trace.
+// Error messages also have a pseudo stack trace, but for panics which already give
+// their location.
 fn error_trace_message(record: &log::Record) -> String {
     match record.level() {
-        Level::Error => format!(
+        Level::Error if !panichook::is_panic(record) => format!(
             "{} -> {}:{}",
             record.args(),
             record.file().unwrap_or("<unknown>"),
@@ -412,26 +437,58 @@ fn error_trace_message(record: &log::Record) -> String {
     }
 }
 
-// Parse level filters from `&str`.
-fn parse_filters(environment_variable_name: &str) -> Level {
-    match environment_variable_name {
-        "trace" => Level::Trace,
-        "debug" => Level::Debug,
-        "info" => Level::Info,
-        "warn" => Level::Warn,
-        "error" => Level::Error,
-        _ => Level::Error,
+// The name of the service: `SERVICE_NAME`, or the name of the crate being run by
+// cargo.
+fn service_name() -> String {
+    env::var("SERVICE_NAME").or_else(|_| env::var("CARGO_PKG_NAME")).unwrap_or_default()
+}
+
+// The version of the service: `SERVICE_VERSION`, or the version of the crate being
+// run by cargo.
+#[cfg(feature = "json")]
+fn service_version() -> String {
+    env::var("SERVICE_VERSION").or_else(|_| env::var("CARGO_PKG_VERSION")).unwrap_or_default()
+}
+
+// The syslog severity of `level`, also used by journald and GELF.
+fn syslog_severity(level: Level) -> u8 {
+    match level {
+        Level::Error => 3,
+        Level::Warn => 4,
+        Level::Info => 6,
+        Level::Debug | Level::Trace => 7,
     }
 }
 
-// Parse format filters from `&str`.
+// Parse format filters from `&str`, where `template:...` gives a line template.
 fn parse_formats(environment_variable_name: &str) -> LogeFormat {
-    match environment_variable_name {
-        "target" => LogeFormat::Target,
-        "fileline" => LogeFormat::Fileline,
+    match try_parse_format(environment_variable_name) {
+        Ok(format) => format,
+        Err(e) if environment_variable_name.starts_with("template:") => {
+            eprintln!("warning: {}, using the target format", e);
+            LogeFormat::Target
+        }
+        Err(_) => LogeFormat::Target,
+    }
+}
+
+// Parse a format name, failing on unknown names and invalid templates.
+fn try_parse_format(s: &str) -> Result<LogeFormat, String> {
+    match s {
+        "target" => Ok(LogeFormat::Target),
+        "fileline" => Ok(LogeFormat::Fileline),
+        #[cfg(feature = "json")]
+        "json" => Ok(LogeFormat::Json),
+        "logfmt" => Ok(LogeFormat::Logfmt),
         #[cfg(feature = "json")]
-        "json" => LogeFormat::Json,
-        _ => LogeFormat::Target,
+        "gelf" => Ok(LogeFormat::Gelf),
+        #[cfg(feature = "json")]
+        "ecs" => Ok(LogeFormat::Ecs),
+        s if s.starts_with("template:") => match s["template:".len()..].parse() {
+            Ok(template) => Ok(LogeFormat::Template(template)),
+            Err(e) => Err(e.to_string()),
+        },
+        _ => Err(format!("unknown log format `{}`", s)),
     }
 }
 
@@ -445,7 +502,6 @@ fn parse_formats(environment_variable_name: &str) -> LogeFormat {
 ///
 /// This function fails to set the global logger if one has already been set.
 #[inline]
-#[cfg(not(feature = "file"))]
 pub fn init() {
     try_init().unwrap();
 }
@@ -460,7 +516,6 @@ pub fn init() {
 ///
 /// This function fails to set the global logger if one has already been set.
 #[inline]
-#[cfg(feature = "file")]
 pub fn init_with_file<P: AsRef<Path>>(log_file: P) {
     try_init_with_file(log_file).unwrap();
 }
@@ -474,8 +529,7 @@ pub fn init_with_file<P: AsRef<Path>>(log_file: P) {
 /// # Errors
 ///
 /// This function fails to set the global logger if one has already been set.
-#[cfg(not(feature = "file"))]
-pub fn try_init() -> Result<(), log::SetLoggerError> {
+pub fn try_init() -> Result<(), SetLoggerError> {
     try_init_custom_env("RUST_LOG", "LOGE_FORMAT")
 }
 
@@ -488,9 +542,7 @@ pub fn try_init() -> Result<(), log::SetLoggerError> {
 /// # Errors
 ///
 /// This function fails to set the global logger if one has already been set.
-/// Initialize a file logger that logs all messages by default.
-#[cfg(feature = "file")]
-pub fn try_init_with_file<P: AsRef<Path>>(log_file: P) -> Result<(), log::SetLoggerError> {
+pub fn try_init_with_file<P: AsRef<Path>>(log_file: P) -> Result<(), SetLoggerError> {
     try_init_custom_env_with_file(log_file, "RUST_LOG", "LOGE_FORMAT")
 }
 
@@ -503,7 +555,6 @@ pub fn try_init_with_file<P: AsRef<Path>>(log_file: P) -> Result<(), log::SetLog
 /// # Panics
 ///
 /// This function fails to set the global logger if one has already been set.
-#[cfg(not(feature = "file"))]
 pub fn init_custom(level: Level, config: LogeFormat) {
     try_init_custom(level, config).unwrap();
 }
@@ -517,7 +568,6 @@ pub fn init_custom(level: Level, config: LogeFormat) {
 /// # Panics
 ///
 /// This function fails to set the global logger if one has already been set.
-#[cfg(feature = "file")]
 pub fn init_custom_with_file<P: AsRef<Path>>(log_file: P, level: Level, config: LogeFormat) {
     try_init_custom_with_file(log_file, level, config).unwrap();
 }
@@ -531,15 +581,8 @@ pub fn init_custom_with_file<P: AsRef<Path>>(log_file: P, level: Level, config:
 /// # Errors
 ///
 /// This function fails to set the global logger if one has already been set.
-#[cfg(not(feature = "file"))]
 pub fn try_init_custom(level: Level, config: LogeFormat) -> Result<(), SetLoggerError> {
-    #[cfg(all(windows, feature = "colored"))]
-    set_up_color_terminal();
-
-    let logger = LogeLogger::new(level, config);
-    log::set_max_level(level.to_level_filter());
-    log::set_boxed_logger(Box::new(logger))?;
-    Ok(())
+    Builder::new().level(level).format(config).try_init().map(|_| ())
 }
 
 /// Initialized the global file logger with a logger named `loge`, with a custom config.
@@ -551,19 +594,12 @@ pub fn try_init_custom(level: Level, config: LogeFormat) -> Result<(), SetLogger
 /// # Errors
 ///
 /// This function fails to set the global logger if one has already been set.
-#[cfg(feature = "file")]
 pub fn try_init_custom_with_file<P: AsRef<Path>>(
     log_file: P,
     level: Level,
     config: LogeFormat,
 ) -> Result<(), SetLoggerError> {
-    #[cfg(all(windows, feature = "colored"))]
-    set_up_color_terminal();
-
-    let logger = LogeLogger::new(level, config, log_file);
-    log::set_max_level(level.to_level_filter());
-    log::set_boxed_logger(Box::new(logger))?;
-    Ok(())
+    Builder::new().level(level).format(config).file(log_file).try_init().map(|_| ())
 }
 
 /// Initialized the global logger with a logger named `loge`, with a custom custom variable
@@ -576,7 +612,6 @@ pub fn try_init_custom_with_file<P: AsRef<Path>>(
 /// # Panics
 ///
 /// This function fails to set the global logger if one has already been set.
-#[cfg(not(feature = "file"))]
 pub fn init_custom_env(environment_variable_log: &str, environment_variable_format: &str) {
     try_init_custom_env(environment_variable_log, environment_variable_format).unwrap();
 }
@@ -591,7 +626,6 @@ pub fn init_custom_env(environment_variable_log: &str, environment_variable_form
 /// # Panics
 ///
 /// This function fails to set the global logger if one has already been set.
-#[cfg(feature = "file")]
 pub fn init_custom_env_with_file<P: AsRef<Path>>(
     log_file: P,
     environment_variable_log: &str,
@@ -611,23 +645,17 @@ pub fn init_custom_env_with_file<P: AsRef<Path>>(
 /// # Errors
 ///
 /// This function fails to set the global logger if one has already been set.
-#[cfg(not(feature = "file"))]
 pub fn try_init_custom_env(
     environment_variable_log: &str,
     environment_variable_format: &str,
 ) -> Result<(), SetLoggerError> {
-    #[cfg(all(windows, feature = "colored"))]
-    set_up_color_terminal();
-
-    if let Ok(s1) = ::std::env::var(environment_variable_log) {
-        if let Ok(s2) = ::std::env::var(environment_variable_format) {
-            let level = parse_filters(&s1);
-            let config = parse_formats(&s2);
-
-            try_init_custom(level, config).unwrap();
-        }
-    }
-    Ok(())
+    Builder::new()
+        .level_env(environment_variable_log)
+        .format_env(environment_variable_format)
+        .stream_env("LOGE_TARGET")
+        .theme_env("LOGE_THEME")
+        .try_init()
+        .map(|_| ())
 }
 
 /// Initialized the global file logger with a logger named `loge`, with a custom custom variable
@@ -640,22 +668,17 @@ pub fn try_init_custom_env(
 /// # Errors
 ///
 /// This function fails to set the global logger if one has already been set.
-#[cfg(feature = "file")]
 pub fn try_init_custom_env_with_file<P: AsRef<Path>>(
     log_file: P,
     environment_variable_log: &str,
     environment_variable_format: &str,
 ) -> Result<(), SetLoggerError> {
-    #[cfg(all(windows, feature = "colored"))]
-    set_up_color_terminal();
-
-    if let Ok(s1) = ::std::env::var(environment_variable_log) {
-        if let Ok(s2) = ::std::env::var(environment_variable_format) {
-            let level = parse_filters(&s1);
-            let config = parse_formats(&s2);
-
-            try_init_custom_with_file(log_file, level, config).unwrap();
-        }
-    }
-    Ok(())
+    Builder::new()
+        .level_env(environment_variable_log)
+        .format_env(environment_variable_format)
+        .stream_env("LOGE_TARGET")
+        .theme_env("LOGE_THEME")
+        .file(log_file)
+        .try_init()
+        .map(|_| ())
 }
@@ -0,0 +1,298 @@
+use crate::color::ColorChoice;
+use crate::error::Destination;
+use crate::filewriter::Rotation;
+use crate::filter::Filter;
+use crate::sink::{FileSink, Sink, StderrSink, StdoutSink};
+use crate::LogeFormat;
+use log::{Level, LevelFilter};
+use std::cell::Cell;
+use std::io::{self, IsTerminal};
+use std::ops::{Deref, DerefMut, RangeInclusive};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+/// `Stream` is used to choose where the console lines go.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Stream {
+    /// every record to standard output
+    #[default]
+    Stdout,
+    /// every record to standard error, keeping standard output for data
+    Stderr,
+    /// `Warn` and `Error` records to standard error, the others to standard output
+    Split,
+}
+
+impl FromStr for Stream {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Stream, String> {
+        match s {
+            "stdout" => Ok(Stream::Stdout),
+            "stderr" => Ok(Stream::Stderr),
+            "split" => Ok(Stream::Split),
+            _ => Err(format!("unknown console stream `{}`", s)),
+        }
+    }
+}
+
+impl Stream {
+    // The console channels, with colors chosen for each stream.
+    pub(crate) fn install(self, color: ColorChoice) -> Vec<Channel> {
+        let stdout = || {
+            let color = color.enabled(Some(io::stdout().is_terminal()));
+            Channel::new(Box::new(StdoutSink), Destination::Console, color)
+        };
+        let stderr = || {
+            let color = color.enabled(Some(io::stderr().is_terminal()));
+            Channel::new(Box::new(StderrSink), Destination::Console, color)
+        };
+        match self {
+            Stream::Stdout => vec![stdout()],
+            Stream::Stderr => vec![stderr()],
+            Stream::Split => {
+                let mut errors = stderr();
+                errors.levels = Level::Error..=Level::Warn;
+                let mut others = stdout();
+                others.levels = Level::Info..=Level::Trace;
+                vec![errors, others]
+            }
+        }
+    }
+}
+
+/// `Output` describes one more destination for the records, with its own level
+/// filter, format and color setting.
+///
+/// Settings that are not given fall back to the ones of the logger: an output
+/// without a level or directives logs what the logger's filter lets through, and
+/// one without a format uses the logger's format. Calling any of the filtering
+/// methods gives the output its own filter, which logs `Level::Error` and above
+/// unless told otherwise.
+///
+/// ## Example
+///
+/// ```no_run
+/// # extern crate loge;
+/// // Colored `Target` lines at `info` on the terminal, `Json` at `debug` in a file.
+/// loge::Builder::new()
+///     .level(log::Level::Info)
+///     .output(
+///         loge::Output::file("debug.json")
+///             .level(log::Level::Debug)
+///             .format(loge::LogeFormat::Json),
+///     )
+///     .init();
+/// ```
+pub struct Output {
+    kind: Kind,
+    filter: Option<Filter>,
+    format: Option<LogeFormat>,
+    color: ColorChoice,
+}
+
+enum Kind {
+    Stdout,
+    Stderr,
+    File { path: PathBuf, rotation: Rotation, reopen_when_moved: bool },
+    Custom(Box<dyn Sink>),
+}
+
+impl Output {
+    /// Write records to standard output.
+    pub fn console() -> Output {
+        Output::new(Kind::Stdout)
+    }
+
+    /// Write records to standard error.
+    pub fn stderr() -> Output {
+        Output::new(Kind::Stderr)
+    }
+
+    /// Write records to the file at `path`.
+    pub fn file<P: AsRef<Path>>(path: P) -> Output {
+        let path = path.as_ref().to_path_buf();
+        Output::new(Kind::File { path, rotation: Rotation::new(), reopen_when_moved: false })
+    }
+
+    /// Write records to a [`Sink`](trait.Sink.html) provided by the application.
+    pub fn sink<S: Sink + 'static>(sink: S) -> Output {
+        Output::new(Kind::Custom(Box::new(sink)))
+    }
+
+    fn new(kind: Kind) -> Output {
+        Output { kind, filter: None, format: None, color: ColorChoice::Auto }
+    }
+
+    fn filter_mut(&mut self) -> &mut Filter {
+        self.filter.get_or_insert_with(|| Filter::new(LevelFilter::Error))
+    }
+
+    /// Set the most verbose level written to this output by default.
+    pub fn level(mut self, level: Level) -> Output {
+        self.filter_mut().insert(None, level.to_level_filter());
+        self
+    }
+
+    /// Set the most verbose level written to this output for `module` and its
+    /// submodules.
+    pub fn filter_module(mut self, module: &str, level: LevelFilter) -> Output {
+        self.filter_mut().insert(Some(module), level);
+        self
+    }
+
+    /// Add directives in the `RUST_LOG` syntax, such as `warn,my_crate=debug`.
+    pub fn parse_filters(mut self, spec: &str) -> Output {
+        self.filter_mut().extend(spec);
+        self
+    }
+
+    /// Set the format of this output.
+    pub fn format(mut self, format: LogeFormat) -> Output {
+        self.format = Some(format);
+        self
+    }
+
+    /// Set whether lines are colored (needs `colored`). By default, only
+    /// terminals get colors.
+    pub fn color(mut self, color: ColorChoice) -> Output {
+        self.color = color;
+        self
+    }
+
+    /// Rotate the file of a [`file`](#method.file) output according to `rotation`.
+    pub fn rotation(mut self, rotation: Rotation) -> Output {
+        if let Kind::File { rotation: ref mut current, .. } = self.kind {
+            *current = rotation;
+        }
+        self
+    }
+
+    /// Reopen the file of a [`file`](#method.file) output when its path no longer
+    /// points to it, see [`Builder::reopen_when_moved`](struct.Builder.html#method.reopen_when_moved).
+    pub fn reopen_when_moved(mut self) -> Output {
+        if let Kind::File { reopen_when_moved: ref mut current, .. } = self.kind {
+            *current = true;
+        }
+        self
+    }
+
+    /// Open the destination.
+    pub(crate) fn install(self) -> Channel {
+        let terminal = match self.kind {
+            Kind::Stdout => Some(io::stdout().is_terminal()),
+            Kind::Stderr => Some(io::stderr().is_terminal()),
+            Kind::File { .. } | Kind::Custom(_) => None,
+        };
+        let (sink, destination): (Box<dyn Sink>, _) = match self.kind {
+            Kind::Stdout => (Box::new(StdoutSink), Destination::Console),
+            Kind::Stderr => (Box::new(StderrSink), Destination::Console),
+            Kind::File { path, rotation, reopen_when_moved } => {
+                let mut sink = FileSink::new(path, rotation);
+                if reopen_when_moved {
+                    sink = sink.reopen_when_moved();
+                }
+                (Box::new(sink), Destination::File)
+            }
+            Kind::Custom(sink) => (sink, Destination::Other),
+        };
+        let mut channel = Channel::new(sink, destination, self.color.enabled(terminal));
+        channel.filter = self.filter;
+        channel.format = self.format;
+        channel
+    }
+}
+
+/// An installed output. Unset settings fall back to the logger's.
+pub(crate) struct Channel {
+    sink: Mutex<Box<dyn Sink>>,
+    /// Which failure counter the sink's errors go to.
+    pub destination: Destination,
+    /// Whether the sink wants a copy of the records.
+    pub structured: bool,
+    /// The levels written to the sink, on top of its filter.
+    pub levels: RangeInclusive<Level>,
+    pub filter: Option<Filter>,
+    pub format: Option<LogeFormat>,
+    pub color: bool,
+    // Cleared while the sink stands for a file not set yet, so records skip it.
+    active: AtomicBool,
+}
+
+impl Channel {
+    pub fn new(sink: Box<dyn Sink>, destination: Destination, color: bool) -> Channel {
+        let structured = sink.structured();
+        Channel {
+            sink: Mutex::new(sink),
+            destination,
+            structured,
+            levels: Level::Error..=Level::Trace,
+            filter: None,
+            format: None,
+            color,
+            active: AtomicBool::new(true),
+        }
+    }
+
+    /// Create a channel holding the place of a file set later.
+    pub fn inactive(sink: Box<dyn Sink>, destination: Destination) -> Channel {
+        let channel = Channel::new(sink, destination, false);
+        channel.active.store(false, Ordering::Relaxed);
+        channel
+    }
+
+    /// Whether records are rendered and written to the sink.
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::Relaxed)
+    }
+
+    /// Set whether records are rendered and written to the sink.
+    #[cfg(feature = "config")]
+    pub fn set_active(&self, active: bool) {
+        self.active.store(active, Ordering::Relaxed);
+    }
+
+    pub fn sink(&self) -> SinkGuard<'_> {
+        let sink = self.sink.lock().unwrap_or_else(|e| e.into_inner());
+        LOCKED_SINKS.with(|locked| locked.set(locked.get() + 1));
+        SinkGuard { sink }
+    }
+}
+
+thread_local! {
+    // How many sinks the thread holds, so that a panic in a sink does not try to
+    // lock it again to log itself.
+    static LOCKED_SINKS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Whether the thread holds a sink, e.g. because it panicked while writing.
+pub(crate) fn in_sink() -> bool {
+    LOCKED_SINKS.with(|locked| locked.get() > 0)
+}
+
+/// A locked sink, counted in `LOCKED_SINKS` until dropped.
+pub(crate) struct SinkGuard<'a> {
+    sink: MutexGuard<'a, Box<dyn Sink>>,
+}
+
+impl Deref for SinkGuard<'_> {
+    type Target = Box<dyn Sink>;
+
+    fn deref(&self) -> &Box<dyn Sink> {
+        &self.sink
+    }
+}
+
+impl DerefMut for SinkGuard<'_> {
+    fn deref_mut(&mut self) -> &mut Box<dyn Sink> {
+        &mut self.sink
+    }
+}
+
+impl Drop for SinkGuard<'_> {
+    fn drop(&mut self) {
+        LOCKED_SINKS.with(|locked| locked.set(locked.get() - 1));
+    }
+}
@@ -0,0 +1,73 @@
+use log::{Level, Record};
+use std::any::Any;
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::{panic, thread};
+
+/// Log panics through `loge` before unwinding.
+///
+/// The panic message, location and thread name are logged as an `Error` record with
+/// the `panic` target in the configured format, followed by a backtrace when one is
+/// captured (set `RUST_BACKTRACE=1`), then every sink is flushed and the previously
+/// installed hook runs, so the default message still reaches stderr. The record is
+/// written whatever the filters, which would otherwise hide panics of other crates.
+///
+/// A panic raised by a sink while writing is left to the previous hook alone, as
+/// logging it would wait for the sink the panicking thread holds.
+///
+/// ## Example
+///
+/// ```
+/// # extern crate loge;
+/// loge::init();
+/// loge::install_panic_hook();
+/// ```
+pub fn install_panic_hook() {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if crate::output::in_sink() {
+            previous(info);
+            return;
+        }
+        let thread = thread::current();
+        let message = payload_message(info.payload());
+        let backtrace = Backtrace::capture();
+        let backtrace = match backtrace.status() {
+            BacktraceStatus::Captured => format!("\nstack backtrace:\n{}", backtrace),
+            _ => String::new(),
+        };
+        let location = info.location();
+        crate::log_unfiltered(
+            &Record::builder()
+                .level(Level::Error)
+                .target("panic")
+                .file(location.map(|l| l.file()))
+                .line(location.map(|l| l.line()))
+                .args(format_args!(
+                    "thread '{}' panicked at '{}', {}{}",
+                    thread.name().unwrap_or("<unnamed>"),
+                    message,
+                    location.map_or_else(|| "<unknown>".to_owned(), |l| l.to_string()),
+                    backtrace
+                ))
+                .build(),
+        );
+        log::logger().flush();
+        previous(info);
+    }));
+}
+
+// Whether `record` comes from the hook, whose message already has the location.
+// Records of the `log` macros always have a module path.
+pub(crate) fn is_panic(record: &Record) -> bool {
+    record.target() == "panic" && record.module_path().is_none()
+}
+
+fn payload_message(payload: &(dyn Any + Send)) -> &str {
+    match payload.downcast_ref::<&str>() {
+        Some(message) => message,
+        None => match payload.downcast_ref::<String>() {
+            Some(message) => message,
+            None => "Box<dyn Any>",
+        },
+    }
+}
@@ -0,0 +1,58 @@
+use crate::LOGGER;
+use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// Incremented to ask every file writer to reopen its path before the next write.
+static REQUESTS: AtomicU64 = AtomicU64::new(0);
+
+/// The number of reopen requests so far.
+pub(crate) fn requests() -> u64 {
+    REQUESTS.load(Ordering::Relaxed)
+}
+
+/// Close the log file and open its path again.
+///
+/// Call it after an external tool such as logrotate renamed the file, so that
+/// records go to the new file instead of the renamed one. Concurrent records are
+/// written either before or after the switch, never lost in between.
+///
+/// ## Example
+///
+/// ```
+/// # extern crate loge;
+/// loge::reopen().expect("failed to reopen the log file");
+/// ```
+pub fn reopen() -> io::Result<()> {
+    match LOGGER.get() {
+        Some(logger) => logger.sinks.reopen(),
+        None => Ok(()),
+    }
+}
+
+/// Reopen the log file whenever the process receives `SIGHUP`, as logrotate's
+/// `postrotate` scripts usually send.
+///
+/// The file is reopened on the next record written after the signal. This
+/// replaces the previous `SIGHUP` handler, which terminates the process by default.
+#[cfg(unix)]
+pub fn reopen_on_sighup() -> io::Result<()> {
+    extern "C" fn handle(_: libc::c_int) {
+        // Only async-signal-safe operations here: writers check the counter.
+        REQUESTS.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // SAFETY: the handler only performs an atomic increment, and the `sigaction`
+    // structure is fully initialized before it is passed to the kernel.
+    let result = unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = handle as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        action.sa_flags = libc::SA_RESTART;
+        libc::sigemptyset(&mut action.sa_mask);
+        libc::sigaction(libc::SIGHUP, &action, std::ptr::null_mut())
+    };
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
@@ -0,0 +1,231 @@
+use crate::filewriter::{FileWriter, Rotation};
+use log::{Level, Record};
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::SystemTime;
+
+/// `Sink` is a destination for formatted records.
+///
+/// Sinks are plugged into the logger with [`Output::sink`](struct.Output.html#method.sink),
+/// which also sets their level filter and format. `loge` calls them from one thread
+/// at a time, either the logging thread or the writer thread of an asynchronous
+/// logger, and handles their errors according to the
+/// [`OnError`](enum.OnError.html) policy.
+///
+/// ## Example
+///
+/// ```
+/// # extern crate loge;
+/// use std::io;
+/// use std::sync::{Arc, Mutex};
+///
+/// // Keep the last lines in memory, e.g. to attach them to crash reports.
+/// struct Ring(Arc<Mutex<Vec<String>>>);
+///
+/// impl loge::Sink for Ring {
+///     fn write(&mut self, line: &str) -> io::Result<()> {
+///         let mut lines = self.0.lock().unwrap();
+///         if lines.len() == 100 {
+///             lines.remove(0);
+///         }
+///         lines.push(line.to_owned());
+///         Ok(())
+///     }
+/// }
+///
+/// let lines = Arc::new(Mutex::new(Vec::new()));
+/// loge::Builder::new().output(loge::Output::sink(Ring(lines.clone()))).init();
+/// ```
+pub trait Sink: Send {
+    /// Write one formatted record, without its trailing newline.
+    fn write(&mut self, line: &str) -> io::Result<()>;
+
+    /// Whether the sink wants the structured record along with the line, through
+    /// [`write_record`](#method.write_record). `false` by default, which spares
+    /// copying records nobody reads. Asked once, when the logger is installed.
+    fn structured(&self) -> bool {
+        false
+    }
+
+    /// Write one formatted record, with a copy of the record it was formatted from.
+    ///
+    /// Only called when [`structured`](#method.structured) returns `true`; writes
+    /// `line` by default.
+    fn write_record(&mut self, line: &str, record: &RecordData) -> io::Result<()> {
+        let _ = record;
+        self.write(line)
+    }
+
+    /// Flush buffered records, e.g. before the program exits.
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Reopen the underlying resource, when [`reopen`](fn.reopen.html) is called.
+    fn reopen(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// `RecordData` is an owned copy of a record, for sinks which need more than the
+/// formatted line.
+#[derive(Debug, Clone)]
+pub struct RecordData {
+    level: Level,
+    target: String,
+    module_path: Option<String>,
+    file: Option<String>,
+    line: Option<u32>,
+    message: String,
+    thread: Option<String>,
+    time: SystemTime,
+    fields: Vec<(String, String)>,
+    context: Vec<(String, String)>,
+}
+
+impl RecordData {
+    /// Copy `record`, logged at `time` with `context`, with the thread of the
+    /// calling thread.
+    pub(crate) fn new(
+        record: &Record,
+        time: SystemTime,
+        context: Vec<(String, String)>,
+    ) -> RecordData {
+        RecordData {
+            level: record.level(),
+            target: record.target().to_owned(),
+            module_path: record.module_path().map(str::to_owned),
+            file: record.file().map(str::to_owned),
+            line: record.line(),
+            message: record.args().to_string(),
+            thread: std::thread::current().name().map(str::to_owned),
+            time,
+            fields: crate::fields::pairs(record),
+            context,
+        }
+    }
+
+    /// The level of the record.
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    /// The target of the record.
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// The module the record was logged from.
+    pub fn module_path(&self) -> Option<&str> {
+        self.module_path.as_deref()
+    }
+
+    /// The source file the record was logged from.
+    pub fn file(&self) -> Option<&str> {
+        self.file.as_deref()
+    }
+
+    /// The source line the record was logged from.
+    pub fn line(&self) -> Option<u32> {
+        self.line
+    }
+
+    /// The message, without key-values.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The name of the thread the record was logged from.
+    pub fn thread(&self) -> Option<&str> {
+        self.thread.as_deref()
+    }
+
+    /// When the record was logged.
+    pub fn time(&self) -> SystemTime {
+        self.time
+    }
+
+    /// The key-values attached to the record (needs `kv`).
+    pub fn fields(&self) -> &[(String, String)] {
+        &self.fields
+    }
+
+    /// The [context](context/index.html) of the thread when the record was logged.
+    pub fn context(&self) -> &[(String, String)] {
+        &self.context
+    }
+}
+
+/// `StdoutSink` writes records to standard output.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StdoutSink;
+
+impl Sink for StdoutSink {
+    fn write(&mut self, line: &str) -> io::Result<()> {
+        writeln!(io::stdout().lock(), "{}", line)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stdout().flush()
+    }
+}
+
+/// `StderrSink` writes records to standard error.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StderrSink;
+
+impl Sink for StderrSink {
+    fn write(&mut self, line: &str) -> io::Result<()> {
+        writeln!(io::stderr().lock(), "{}", line)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stderr().flush()
+    }
+}
+
+/// `FileSink` appends records to a file, rotating it according to a
+/// [`Rotation`](struct.Rotation.html) policy.
+#[derive(Debug)]
+pub struct FileSink {
+    writer: FileWriter,
+}
+
+impl FileSink {
+    /// Open the file at `path` for appending, creating it if needed.
+    ///
+    /// Errors opening the file are reported by the first write, which tries again.
+    pub fn new<P: AsRef<Path>>(path: P, rotation: Rotation) -> FileSink {
+        FileSink { writer: FileWriter::new(path.as_ref().to_path_buf(), rotation) }
+    }
+
+    /// Reopen the file when its path no longer points to it, see
+    /// [`Builder::reopen_when_moved`](struct.Builder.html#method.reopen_when_moved).
+    pub fn reopen_when_moved(mut self) -> FileSink {
+        self.writer.reopen_when_moved();
+        self
+    }
+}
+
+impl Sink for FileSink {
+    fn write(&mut self, line: &str) -> io::Result<()> {
+        self.writer.write(line)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    fn reopen(&mut self) -> io::Result<()> {
+        self.writer.reopen()
+    }
+}
+
+// Stands for the file set with `Builder::file` while there is none.
+pub(crate) struct NoSink;
+
+impl Sink for NoSink {
+    fn write(&mut self, _: &str) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,316 @@
+use crate::sink::{RecordData, Sink};
+use crate::tcp::TcpConnection;
+use crate::utc::Utc;
+use log::Level;
+use std::io;
+use std::net::{ToSocketAddrs, UdpSocket};
+#[cfg(unix)]
+use std::os::unix::net::UnixDatagram;
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+/// `Facility` is used to choose the syslog facility, which tells the daemon what
+/// kind of program sent a message.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Facility {
+    /// kernel messages
+    Kern = 0,
+    /// user-level messages
+    #[default]
+    User = 1,
+    /// mail system
+    Mail = 2,
+    /// system daemons
+    Daemon = 3,
+    /// security and authorization messages
+    Auth = 4,
+    /// messages generated by the syslog daemon
+    Syslog = 5,
+    /// line printer subsystem
+    Lpr = 6,
+    /// network news subsystem
+    News = 7,
+    /// UUCP subsystem
+    Uucp = 8,
+    /// clock daemon
+    Cron = 9,
+    /// private security and authorization messages
+    AuthPriv = 10,
+    /// FTP daemon
+    Ftp = 11,
+    /// local use 0
+    Local0 = 16,
+    /// local use 1
+    Local1 = 17,
+    /// local use 2
+    Local2 = 18,
+    /// local use 3
+    Local3 = 19,
+    /// local use 4
+    Local4 = 20,
+    /// local use 5
+    Local5 = 21,
+    /// local use 6
+    Local6 = 22,
+    /// local use 7
+    Local7 = 23,
+}
+
+/// `SyslogProtocol` is used to choose the layout of syslog messages.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum SyslogProtocol {
+    /// the BSD layout, `<PRI>Mmm dd hh:mm:ss HOSTNAME APP[PROCID]: MSG`, which every
+    /// daemon understands, with the local time (UTC without `chrono`)
+    Rfc3164,
+    /// the IETF layout, `<PRI>1 TIMESTAMP HOSTNAME APP PROCID - - MSG`, with the
+    /// year and milliseconds of the time
+    #[default]
+    Rfc5424,
+}
+
+enum Transport {
+    #[cfg(unix)]
+    Unix(PathBuf),
+    Udp(String),
+    Tcp(String),
+}
+
+enum Connection {
+    #[cfg(unix)]
+    Unix(UnixDatagram),
+    Udp(UdpSocket),
+    Tcp(TcpConnection),
+}
+
+/// `SyslogSink` sends records to a syslog daemon.
+///
+/// Records go to the local daemon through a Unix socket such as `/dev/log`, or to
+/// a remote one over UDP or TCP. Over TCP, messages are framed by octet counting
+/// (RFC 6587), so they may span several lines. The connection is opened on the
+/// first record, and opened again after an error. Connecting and writing over TCP
+/// give up after 5 seconds, and for 5 seconds after a failure, records fail at
+/// once rather than wait for an unreachable daemon; they go to the
+/// [`OnError`](enum.OnError.html) policy.
+///
+/// The formatted line is sent as the message, and the syslog header carries the
+/// time and severity, so a format with the message alone is usually best.
+///
+/// ## Example
+///
+/// ```no_run
+/// # extern crate loge;
+/// let syslog = loge::SyslogSink::udp("logs.example.com:514")
+///     .protocol(loge::SyslogProtocol::Rfc5424)
+///     .facility(loge::Facility::Local0);
+/// loge::Builder::new()
+///     .output(
+///         loge::Output::sink(syslog)
+///             .format(loge::LogeFormat::Template("{message}".parse().unwrap())),
+///     )
+///     .init();
+/// ```
+pub struct SyslogSink {
+    transport: Transport,
+    connection: Option<Connection>,
+    protocol: SyslogProtocol,
+    facility: Facility,
+    app_name: String,
+    hostname: String,
+    procid: String,
+}
+
+impl SyslogSink {
+    /// Send records to the local daemon listening on `/dev/log`.
+    #[cfg(unix)]
+    pub fn local() -> SyslogSink {
+        SyslogSink::unix("/dev/log")
+    }
+
+    /// Send records to the daemon listening on the Unix datagram socket at `path`.
+    #[cfg(unix)]
+    pub fn unix<P: Into<PathBuf>>(path: P) -> SyslogSink {
+        SyslogSink::new(Transport::Unix(path.into()))
+    }
+
+    /// Send records over UDP to `address`, e.g. `logs.example.com:514`.
+    pub fn udp<A: Into<String>>(address: A) -> SyslogSink {
+        SyslogSink::new(Transport::Udp(address.into()))
+    }
+
+    /// Send records over TCP to `address`, e.g. `logs.example.com:601`.
+    pub fn tcp<A: Into<String>>(address: A) -> SyslogSink {
+        SyslogSink::new(Transport::Tcp(address.into()))
+    }
+
+    fn new(transport: Transport) -> SyslogSink {
+        SyslogSink {
+            transport,
+            connection: None,
+            protocol: SyslogProtocol::default(),
+            facility: Facility::default(),
+            app_name: crate::service_name(),
+            hostname: hostname(),
+            procid: std::process::id().to_string(),
+        }
+    }
+
+    /// Set the layout of the messages, RFC 5424 by default.
+    pub fn protocol(mut self, protocol: SyslogProtocol) -> SyslogSink {
+        self.protocol = protocol;
+        self
+    }
+
+    /// Set the facility, `User` by default.
+    pub fn facility(mut self, facility: Facility) -> SyslogSink {
+        self.facility = facility;
+        self
+    }
+
+    /// Set the name of the application, `SERVICE_NAME` or `CARGO_PKG_NAME` by
+    /// default.
+    pub fn app_name<S: Into<String>>(mut self, app_name: S) -> SyslogSink {
+        self.app_name = app_name.into();
+        self
+    }
+
+    /// Set the name of the host, the one of the machine by default.
+    pub fn hostname<S: Into<String>>(mut self, hostname: S) -> SyslogSink {
+        self.hostname = hostname.into();
+        self
+    }
+
+    /// Set the process id, the one of the process by default.
+    pub fn procid<S: Into<String>>(mut self, procid: S) -> SyslogSink {
+        self.procid = procid.into();
+        self
+    }
+
+    // Lay out `message` with the syslog header.
+    fn message(&self, level: Level, time: SystemTime, message: &str) -> String {
+        let pri = self.facility as u8 * 8 + crate::syslog_severity(level);
+        match self.protocol {
+            SyslogProtocol::Rfc3164 => format!(
+                "<{}>{} {} {}[{}]: {}",
+                pri,
+                bsd_time(time),
+                header_field(&self.hostname, 255),
+                header_field(&self.app_name, 32),
+                header_field(&self.procid, 128),
+                message
+            ),
+            SyslogProtocol::Rfc5424 => format!(
+                "<{}>1 {} {} {} {} - - {}",
+                pri,
+                Utc::new(time),
+                header_field(&self.hostname, 255),
+                header_field(&self.app_name, 48),
+                header_field(&self.procid, 128),
+                message
+            ),
+        }
+    }
+
+    fn connect(&self) -> io::Result<Connection> {
+        match self.transport {
+            #[cfg(unix)]
+            Transport::Unix(_) => UnixDatagram::unbound().map(Connection::Unix),
+            Transport::Udp(ref address) => {
+                let target = address.to_socket_addrs()?.next().ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::NotFound, format!("cannot resolve {}", address))
+                })?;
+                let local = if target.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+                let socket = UdpSocket::bind(local)?;
+                socket.connect(target)?;
+                Ok(Connection::Udp(socket))
+            }
+            Transport::Tcp(ref address) => Ok(Connection::Tcp(TcpConnection::new(address.clone()))),
+        }
+    }
+
+    fn send(&mut self, message: &str) -> io::Result<()> {
+        if self.connection.is_none() {
+            self.connection = Some(self.connect()?);
+        }
+        let result = match (self.connection.as_mut(), &self.transport) {
+            #[cfg(unix)]
+            (Some(Connection::Unix(socket)), Transport::Unix(path)) => {
+                socket.send_to(message.as_bytes(), path).map(drop)
+            }
+            (Some(Connection::Udp(socket)), _) => socket.send(message.as_bytes()).map(drop),
+            (Some(Connection::Tcp(connection)), _) => {
+                connection.write_all(format!("{} {}", message.len(), message).as_bytes())
+            }
+            _ => Ok(()),
+        };
+        // A TCP connection waits before connecting again on its own.
+        if result.is_err() && !matches!(self.connection, Some(Connection::Tcp(_))) {
+            self.connection = None;
+        }
+        result
+    }
+}
+
+impl Sink for SyslogSink {
+    fn write(&mut self, line: &str) -> io::Result<()> {
+        let message = self.message(Level::Info, SystemTime::now(), line);
+        self.send(&message)
+    }
+
+    fn structured(&self) -> bool {
+        true
+    }
+
+    fn write_record(&mut self, line: &str, record: &RecordData) -> io::Result<()> {
+        let message = self.message(record.level(), record.time(), line);
+        self.send(&message)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.connection {
+            Some(Connection::Tcp(ref mut connection)) => connection.flush(),
+            _ => Ok(()),
+        }
+    }
+
+    fn reopen(&mut self) -> io::Result<()> {
+        self.connection = None;
+        Ok(())
+    }
+}
+
+// The time as in RFC 3164, e.g. `Oct  8 09:21:07`, which has no zone and is read as
+// the local time of the receiver.
+fn bsd_time(time: SystemTime) -> String {
+    #[cfg(feature = "chrono")]
+    return chrono::DateTime::<chrono::Local>::from(time).format("%b %e %H:%M:%S").to_string();
+    #[cfg(not(feature = "chrono"))]
+    Utc::new(time).bsd()
+}
+
+// A header field: printable ASCII without spaces, at most `max` characters, or
+// `-` when empty.
+fn header_field(value: &str, max: usize) -> String {
+    let field: String = value.chars().filter(|c| c.is_ascii_graphic()).take(max).collect();
+    if field.is_empty() {
+        "-".to_owned()
+    } else {
+        field
+    }
+}
+
+// The name of the machine.
+pub(crate) fn hostname() -> String {
+    #[cfg(unix)]
+    {
+        let mut buf = [0u8; 256];
+        // SAFETY: the buffer is valid for writes of its whole length, and a name
+        // filling it without a NUL is cut at its end.
+        let result = unsafe { libc::gethostname(buf.as_mut_ptr() as *mut libc::c_char, buf.len()) };
+        if result == 0 {
+            let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+            return String::from_utf8_lossy(&buf[..len]).into_owned();
+        }
+    }
+    std::env::var("HOSTNAME").or_else(|_| std::env::var("COMPUTERNAME")).unwrap_or_default()
+}
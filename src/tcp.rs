@@ -0,0 +1,74 @@
+//! A TCP connection to a log collector, opened on demand and kept from stalling the
+//! threads that log when the collector is unreachable.
+
+use std::io::{self, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+
+// How long connecting, and then each write, may take.
+const TIMEOUT: Duration = Duration::from_secs(5);
+// How long records fail at once after a failure, before connecting again.
+const RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+pub(crate) struct TcpConnection {
+    address: String,
+    stream: Option<TcpStream>,
+    // When the connection last failed.
+    failed_at: Option<Instant>,
+}
+
+impl TcpConnection {
+    pub fn new(address: String) -> TcpConnection {
+        TcpConnection { address, stream: None, failed_at: None }
+    }
+
+    /// Write `data`, connecting first if needed. After an error, the connection is
+    /// closed and writes fail without trying to connect for `RETRY_INTERVAL`.
+    pub fn write_all(&mut self, data: &[u8]) -> io::Result<()> {
+        let result = self.stream().and_then(|stream| stream.write_all(data));
+        if result.is_err() {
+            self.stream = None;
+            self.failed_at = Some(Instant::now());
+        }
+        result
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        match self.stream {
+            Some(ref mut stream) => stream.flush(),
+            None => Ok(()),
+        }
+    }
+
+    fn stream(&mut self) -> io::Result<&mut TcpStream> {
+        if self.stream.is_none() {
+            if let Some(failed_at) = self.failed_at {
+                if failed_at.elapsed() < RETRY_INTERVAL {
+                    return Err(io::Error::new(
+                        io::ErrorKind::NotConnected,
+                        format!("not connected to {}, retrying shortly", self.address),
+                    ));
+                }
+            }
+            self.stream = Some(self.connect()?);
+        }
+        Ok(self.stream.as_mut().unwrap())
+    }
+
+    // Try every address `address` resolves to, returning the last error.
+    fn connect(&self) -> io::Result<TcpStream> {
+        let mut error = None;
+        for target in self.address.to_socket_addrs()? {
+            match TcpStream::connect_timeout(&target, TIMEOUT) {
+                Ok(stream) => {
+                    stream.set_write_timeout(Some(TIMEOUT))?;
+                    return Ok(stream);
+                }
+                Err(e) => error = Some(e),
+            }
+        }
+        Err(error.unwrap_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, format!("cannot resolve {}", self.address))
+        }))
+    }
+}
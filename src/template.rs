@@ -0,0 +1,268 @@
+use crate::color::{self, Painted};
+use crate::formatter::FormatContext;
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Local};
+use log::Record;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+// Color names and their SGR parameters.
+const COLORS: [(&str, &str); 16] = [
+    ("black", "30"),
+    ("red", "31"),
+    ("green", "32"),
+    ("yellow", "33"),
+    ("blue", "34"),
+    ("magenta", "35"),
+    ("cyan", "36"),
+    ("white", "37"),
+    ("bright_black", "90"),
+    ("bright_red", "91"),
+    ("bright_green", "92"),
+    ("bright_yellow", "93"),
+    ("bright_blue", "94"),
+    ("bright_magenta", "95"),
+    ("bright_cyan", "96"),
+    ("bright_white", "97"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    Time,
+    Level,
+    Target,
+    File,
+    Line,
+    Thread,
+    Pid,
+    Message,
+    Fields,
+    Context,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Align {
+    Left,
+    Right,
+    Center,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Style {
+    Bold,
+    Dimmed,
+    Italic,
+    Underline,
+    // The color `loge` uses for the level of the record.
+    LevelColor,
+    Color(&'static str),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Piece {
+    Literal(String),
+    Placeholder { field: Field, align: Align, width: usize, styles: Vec<Style> },
+}
+
+/// `TemplateError` is returned when a line template cannot be parsed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateError {
+    message: String,
+}
+
+impl TemplateError {
+    fn new<S: Into<String>>(message: S) -> TemplateError {
+        TemplateError { message: message.into() }
+    }
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid log template: {}", self.message)
+    }
+}
+
+impl Error for TemplateError {}
+
+/// A user-defined line layout, parsed once and reused for every record.
+///
+/// Placeholders are written between braces, and `{{` and `}}` stand for literal
+/// braces. The available placeholders are `{time}` (needs `chrono`), `{level}`,
+/// `{target}`, `{file}`, `{line}`, `{thread}`, `{pid}`, `{message}`, `{context}` (the
+/// thread's [context](context/index.html) as `key=value` pairs) and `{fields}` (the
+/// record's key-values as `key=value` pairs, needs `kv`).
+///
+/// A placeholder may be followed by `:` and a comma-separated list of modifiers:
+/// an alignment and width such as `<5`, `>8` or `^10`, and styles among `bold`,
+/// `dimmed`, `italic`, `underline`, `level` (the color of the record's level) and
+/// the color names `red`, `bright_black`, etc. Styles are only applied to colored
+/// terminal output.
+///
+/// ## Example
+///
+/// ```
+/// # extern crate loge;
+/// let template: loge::Template = "[{level:<5,level}] {target:bold} - {message}".parse().unwrap();
+/// loge::Builder::new().format(loge::LogeFormat::Template(template)).init();
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    pieces: Vec<Piece>,
+}
+
+impl FromStr for Template {
+    type Err = TemplateError;
+
+    fn from_str(s: &str) -> Result<Template, TemplateError> {
+        let mut pieces = Vec::new();
+        let mut literal = String::new();
+        let mut chars = s.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.as_str().starts_with('{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.as_str().starts_with('}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let rest = chars.as_str();
+                    let end = rest.find('}').ok_or_else(|| TemplateError::new("unclosed `{`"))?;
+                    if !literal.is_empty() {
+                        pieces.push(Piece::Literal(literal.split_off(0)));
+                    }
+                    pieces.push(parse_placeholder(&rest[..end])?);
+                    chars = rest[end + 1..].chars();
+                }
+                '}' => return Err(TemplateError::new("unmatched `}`")),
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            pieces.push(Piece::Literal(literal));
+        }
+        Ok(Template { pieces })
+    }
+}
+
+fn parse_placeholder(spec: &str) -> Result<Piece, TemplateError> {
+    let mut parts = spec.splitn(2, ':');
+    let name = parts.next().unwrap_or_default().trim();
+    let field = match name {
+        #[cfg(feature = "chrono")]
+        "time" => Field::Time,
+        #[cfg(not(feature = "chrono"))]
+        "time" => return Err(TemplateError::new("`{time}` requires the `chrono` feature")),
+        "level" => Field::Level,
+        "target" => Field::Target,
+        "file" => Field::File,
+        "line" => Field::Line,
+        "thread" => Field::Thread,
+        "pid" => Field::Pid,
+        "message" => Field::Message,
+        "context" => Field::Context,
+        #[cfg(feature = "kv")]
+        "fields" => Field::Fields,
+        #[cfg(not(feature = "kv"))]
+        "fields" => return Err(TemplateError::new("`{fields}` requires the `kv` feature")),
+        _ => return Err(TemplateError::new(format!("unknown placeholder `{{{}}}`", name))),
+    };
+
+    let mut align = Align::Left;
+    let mut width = 0;
+    let mut styles = Vec::new();
+    for modifier in parts.next().unwrap_or_default().split(',').map(str::trim) {
+        let digits = modifier.trim_start_matches(['<', '>', '^']);
+        if modifier.is_empty() {
+            continue;
+        } else if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) {
+            align = match modifier.as_bytes()[0] {
+                b'>' => Align::Right,
+                b'^' => Align::Center,
+                _ => Align::Left,
+            };
+            width = digits.parse().map_err(|_| TemplateError::new("width is too large"))?;
+        } else {
+            styles.push(match modifier {
+                "bold" => Style::Bold,
+                "dimmed" => Style::Dimmed,
+                "italic" => Style::Italic,
+                "underline" => Style::Underline,
+                "level" => Style::LevelColor,
+                _ => match COLORS.iter().find(|&&(name, _)| name == modifier) {
+                    Some(&(_, sgr)) => Style::Color(sgr),
+                    None => {
+                        return Err(TemplateError::new(format!("unknown modifier `{}`", modifier)))
+                    }
+                },
+            });
+        }
+    }
+    Ok(Piece::Placeholder { field, align, width, styles })
+}
+
+impl Template {
+    /// Render `record` according to the template into `buf`, with styles if the
+    /// context has a theme.
+    pub(crate) fn render(
+        &self,
+        record: &Record,
+        cx: &FormatContext,
+        buf: &mut Vec<u8>,
+    ) -> io::Result<()> {
+        for piece in &self.pieces {
+            let (field, align, width, styles) = match piece {
+                Piece::Literal(literal) => {
+                    buf.extend_from_slice(literal.as_bytes());
+                    continue;
+                }
+                Piece::Placeholder { field, align, width, styles } => {
+                    (field, align, *width, styles)
+                }
+            };
+            let value = match field {
+                #[cfg(feature = "chrono")]
+                Field::Time => {
+                    DateTime::<Local>::from(cx.time()).format("%Y-%m-%d %H:%M:%S%.3f").to_string()
+                }
+                #[cfg(not(feature = "chrono"))]
+                Field::Time => String::new(),
+                Field::Level => record.level().to_string(),
+                Field::Target => crate::record_target(record).to_owned(),
+                Field::File => record.file().unwrap_or("<unknown>").to_owned(),
+                Field::Line => record.line().map_or(-1, |v| v as i32).to_string(),
+                Field::Thread => std::thread::current().name().unwrap_or("<unnamed>").to_owned(),
+                Field::Pid => std::process::id().to_string(),
+                Field::Message => record.args().to_string(),
+                Field::Fields => crate::fields::text(record),
+                Field::Context => crate::context::text(cx.context()),
+            };
+            let value = match align {
+                Align::Left => format!("{:<width$}", value, width = width),
+                Align::Right => format!("{:>width$}", value, width = width),
+                Align::Center => format!("{:^width$}", value, width = width),
+            };
+            if let (Some(theme), false) = (cx.theme(), styles.is_empty()) {
+                let sgr: Vec<&str> = styles
+                    .iter()
+                    .map(|style| match style {
+                        Style::Bold => color::BOLD,
+                        Style::Dimmed => color::DIMMED,
+                        Style::Italic => color::ITALIC,
+                        Style::Underline => color::UNDERLINE,
+                        Style::LevelColor => theme.level_style(record.level()).sgr(),
+                        Style::Color(sgr) => sgr,
+                    })
+                    .collect();
+                write!(buf, "{}", Painted::new(&sgr.join(";"), value))?;
+                continue;
+            }
+            buf.extend_from_slice(value.as_bytes());
+        }
+        Ok(())
+    }
+}
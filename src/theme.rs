@@ -0,0 +1,385 @@
+use crate::color::Painted;
+use log::Level;
+use std::fmt::{self, Write};
+use std::str::FromStr;
+
+/// `Color` is used to choose the color of a [`Style`](struct.Style.html).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Color {
+    /// black
+    Black,
+    /// red
+    Red,
+    /// green
+    Green,
+    /// yellow
+    Yellow,
+    /// blue
+    Blue,
+    /// magenta
+    Magenta,
+    /// cyan
+    Cyan,
+    /// white
+    White,
+    /// bright black, which most terminals show as gray
+    BrightBlack,
+    /// bright red
+    BrightRed,
+    /// bright green
+    BrightGreen,
+    /// bright yellow
+    BrightYellow,
+    /// bright blue
+    BrightBlue,
+    /// bright magenta
+    BrightMagenta,
+    /// bright cyan
+    BrightCyan,
+    /// bright white
+    BrightWhite,
+    /// one of the 256 colors of the xterm palette
+    Fixed(u8),
+    /// a 24-bit color, for terminals supporting truecolor
+    Rgb(u8, u8, u8),
+}
+
+impl Color {
+    // Append the SGR parameters of the color as a foreground.
+    fn push_sgr(self, sgr: &mut String) {
+        let basic = |code: u8| code.to_string();
+        let code = match self {
+            Color::Black => basic(30),
+            Color::Red => basic(31),
+            Color::Green => basic(32),
+            Color::Yellow => basic(33),
+            Color::Blue => basic(34),
+            Color::Magenta => basic(35),
+            Color::Cyan => basic(36),
+            Color::White => basic(37),
+            Color::BrightBlack => basic(90),
+            Color::BrightRed => basic(91),
+            Color::BrightGreen => basic(92),
+            Color::BrightYellow => basic(93),
+            Color::BrightBlue => basic(94),
+            Color::BrightMagenta => basic(95),
+            Color::BrightCyan => basic(96),
+            Color::BrightWhite => basic(97),
+            Color::Fixed(n) => format!("38;5;{}", n),
+            Color::Rgb(r, g, b) => format!("38;2;{};{};{}", r, g, b),
+        };
+        push_param(sgr, &code);
+    }
+}
+
+/// `Style` is used to choose how one part of a line looks: its color and
+/// attributes.
+///
+/// ## Example
+///
+/// ```
+/// # extern crate loge;
+/// let orange = loge::Style::new().fg(loge::Color::Rgb(0xcb, 0x4b, 0x16)).bold();
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Style {
+    fg: Option<Color>,
+    bold: bool,
+    dimmed: bool,
+    italic: bool,
+    underline: bool,
+    // The SGR parameters, kept up to date by the setters.
+    sgr: String,
+}
+
+impl Style {
+    /// Create a style leaving the text as it is.
+    pub fn new() -> Style {
+        Style::default()
+    }
+
+    /// Set the color of the text.
+    pub fn fg(mut self, color: Color) -> Style {
+        self.fg = Some(color);
+        self.update()
+    }
+
+    /// Make the text bold.
+    pub fn bold(mut self) -> Style {
+        self.bold = true;
+        self.update()
+    }
+
+    /// Make the text dimmed.
+    pub fn dimmed(mut self) -> Style {
+        self.dimmed = true;
+        self.update()
+    }
+
+    /// Make the text italic.
+    pub fn italic(mut self) -> Style {
+        self.italic = true;
+        self.update()
+    }
+
+    /// Underline the text.
+    pub fn underline(mut self) -> Style {
+        self.underline = true;
+        self.update()
+    }
+
+    fn update(mut self) -> Style {
+        let mut sgr = String::new();
+        for (set, code) in [
+            (self.bold, crate::color::BOLD),
+            (self.dimmed, crate::color::DIMMED),
+            (self.italic, crate::color::ITALIC),
+            (self.underline, crate::color::UNDERLINE),
+        ] {
+            if set {
+                push_param(&mut sgr, code);
+            }
+        }
+        if let Some(color) = self.fg {
+            color.push_sgr(&mut sgr);
+        }
+        self.sgr = sgr;
+        self
+    }
+
+    /// Display `value` in this style, keeping the width and alignment it is
+    /// formatted with.
+    pub fn paint<'a, T: fmt::Display + 'a>(&'a self, value: T) -> impl fmt::Display + 'a {
+        Painted::new(&self.sgr, value)
+    }
+
+    pub(crate) fn sgr(&self) -> &str {
+        &self.sgr
+    }
+}
+
+fn push_param(sgr: &mut String, param: &str) {
+    if !sgr.is_empty() {
+        sgr.push(';');
+    }
+    let _ = write!(sgr, "{}", param);
+}
+
+/// `Theme` is used to choose the colors of colored lines.
+///
+/// It styles the level, time, target, file and line of the `target`, `fileline`
+/// and template layouts, and the keys, strings and numbers of the `json` layout.
+/// Start from one of the presets, which are also available by name for the
+/// `LOGE_THEME` environment variable, and change the parts you want:
+///
+/// - `default`: the classic 16 colors;
+/// - `solarized`: the truecolor palette of Solarized;
+/// - `high-contrast`: bright and bold colors for dim screens;
+/// - `monochrome-bold`: attributes only, for terminals without colors.
+///
+/// ## Example
+///
+/// ```
+/// # extern crate loge;
+/// let theme = loge::Theme::solarized()
+///     .level(log::Level::Info, loge::Style::new().fg(loge::Color::Fixed(34)));
+/// loge::Builder::new().theme(theme).init();
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    levels: [Style; 5],
+    time: Style,
+    target: Style,
+    file: Style,
+    line: Style,
+    key: Style,
+    string: Style,
+    number: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        let fg = |color| Style::new().fg(color);
+        Theme {
+            levels: [
+                fg(Color::Red),
+                fg(Color::Yellow),
+                fg(Color::Green),
+                fg(Color::Blue),
+                fg(Color::Cyan),
+            ],
+            time: fg(Color::BrightBlack),
+            target: Style::new().bold(),
+            file: Style::new().bold(),
+            line: fg(Color::Magenta),
+            key: fg(Color::Cyan),
+            string: fg(Color::Green),
+            number: fg(Color::Magenta),
+        }
+    }
+}
+
+impl Theme {
+    /// The Solarized palette, in truecolor.
+    pub fn solarized() -> Theme {
+        let fg = |r, g, b| Style::new().fg(Color::Rgb(r, g, b));
+        let (blue, cyan, magenta) =
+            (fg(0x26, 0x8b, 0xd2), fg(0x2a, 0xa1, 0x98), fg(0xd3, 0x36, 0x82));
+        Theme {
+            levels: [
+                fg(0xdc, 0x32, 0x2f).bold(),
+                fg(0xcb, 0x4b, 0x16),
+                fg(0x85, 0x99, 0x00),
+                blue.clone(),
+                fg(0x6c, 0x71, 0xc4),
+            ],
+            time: fg(0x58, 0x6e, 0x75),
+            target: fg(0xb5, 0x89, 0x00).bold(),
+            file: cyan.clone(),
+            line: magenta.clone(),
+            key: blue,
+            string: cyan,
+            number: magenta,
+        }
+    }
+
+    /// Bright and bold colors of the 256-color palette, readable on dim screens
+    /// and projectors.
+    pub fn high_contrast() -> Theme {
+        let fg = |n| Style::new().fg(Color::Fixed(n)).bold();
+        Theme {
+            levels: [fg(196), fg(226), fg(46), fg(51), fg(201)],
+            time: Style::new().fg(Color::Fixed(255)),
+            target: fg(231),
+            file: fg(231),
+            line: fg(213),
+            key: fg(51),
+            string: fg(118),
+            number: fg(226),
+        }
+    }
+
+    /// No colors, only attributes: errors are bold and underlined, warnings and
+    /// targets bold, and debug and trace records dimmed.
+    pub fn monochrome_bold() -> Theme {
+        let plain = Style::new;
+        Theme {
+            levels: [
+                Style::new().bold().underline(),
+                Style::new().bold(),
+                plain(),
+                Style::new().dimmed(),
+                Style::new().dimmed(),
+            ],
+            time: Style::new().dimmed(),
+            target: Style::new().bold(),
+            file: plain(),
+            line: plain(),
+            key: Style::new().bold(),
+            string: plain(),
+            number: plain(),
+        }
+    }
+
+    /// Set the style of `level`.
+    pub fn level(mut self, level: Level, style: Style) -> Theme {
+        self.levels[level as usize - 1] = style;
+        self
+    }
+
+    /// Set the style of the time.
+    pub fn time(mut self, style: Style) -> Theme {
+        self.time = style;
+        self
+    }
+
+    /// Set the style of the target.
+    pub fn target(mut self, style: Style) -> Theme {
+        self.target = style;
+        self
+    }
+
+    /// Set the style of the source file.
+    pub fn file(mut self, style: Style) -> Theme {
+        self.file = style;
+        self
+    }
+
+    /// Set the style of the source line.
+    pub fn line(mut self, style: Style) -> Theme {
+        self.line = style;
+        self
+    }
+
+    /// Set the style of JSON keys.
+    pub fn key(mut self, style: Style) -> Theme {
+        self.key = style;
+        self
+    }
+
+    /// Set the style of JSON strings.
+    pub fn string(mut self, style: Style) -> Theme {
+        self.string = style;
+        self
+    }
+
+    /// Set the style of JSON numbers.
+    pub fn number(mut self, style: Style) -> Theme {
+        self.number = style;
+        self
+    }
+
+    /// The style of `level`.
+    pub fn level_style(&self, level: Level) -> &Style {
+        &self.levels[level as usize - 1]
+    }
+
+    /// The style of the time.
+    pub fn time_style(&self) -> &Style {
+        &self.time
+    }
+
+    /// The style of the target.
+    pub fn target_style(&self) -> &Style {
+        &self.target
+    }
+
+    /// The style of the source file.
+    pub fn file_style(&self) -> &Style {
+        &self.file
+    }
+
+    /// The style of the source line.
+    pub fn line_style(&self) -> &Style {
+        &self.line
+    }
+
+    /// The style of JSON keys.
+    pub fn key_style(&self) -> &Style {
+        &self.key
+    }
+
+    /// The style of JSON strings.
+    pub fn string_style(&self) -> &Style {
+        &self.string
+    }
+
+    /// The style of JSON numbers.
+    pub fn number_style(&self) -> &Style {
+        &self.number
+    }
+}
+
+impl FromStr for Theme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Theme, String> {
+        match s {
+            "default" => Ok(Theme::default()),
+            "solarized" => Ok(Theme::solarized()),
+            "high-contrast" => Ok(Theme::high_contrast()),
+            "monochrome-bold" => Ok(Theme::monochrome_bold()),
+            _ => Err(format!("unknown theme `{}`", s)),
+        }
+    }
+}
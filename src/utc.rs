@@ -0,0 +1,71 @@
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(not(feature = "chrono"))]
+const MONTHS: [&str; 12] =
+    ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+/// A point in time split into its UTC calendar fields, so that timestamps do not
+/// need `chrono`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Utc {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+    pub millis: u32,
+}
+
+impl Utc {
+    pub fn new(time: SystemTime) -> Utc {
+        let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let secs = since_epoch.as_secs() as i64;
+        let (days, rest) = (secs.div_euclid(86_400), secs.rem_euclid(86_400) as u32);
+        // Convert days since the epoch to a civil date, after Howard Hinnant's
+        // `civil_from_days`.
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = yoe + era * 400 + i64::from(month <= 2);
+        Utc {
+            year,
+            month,
+            day,
+            hour: rest / 3600,
+            minute: rest / 60 % 60,
+            second: rest % 60,
+            millis: since_epoch.subsec_millis(),
+        }
+    }
+
+    /// The time as in RFC 3164, e.g. `Oct  8 07:21:07`.
+    #[cfg(not(feature = "chrono"))]
+    pub fn bsd(self) -> String {
+        format!(
+            "{} {:>2} {:02}:{:02}:{:02}",
+            MONTHS[self.month as usize - 1],
+            self.day,
+            self.hour,
+            self.minute,
+            self.second
+        )
+    }
+}
+
+/// RFC 3339 with milliseconds, e.g. `2026-10-08T07:21:07.412Z`.
+impl fmt::Display for Utc {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+            self.year, self.month, self.day, self.hour, self.minute, self.second, self.millis
+        )
+    }
+}
@@ -0,0 +1,48 @@
+#[cfg(test)]
+mod tests {
+    extern crate loge;
+    use log::error;
+    use std::time::{Duration, Instant};
+    use std::{env, fs, thread};
+
+    #[test]
+    fn test_async_dropped_loge() {
+        let path = env::temp_dir().join(format!("loge-async-dropped-{}.log", std::process::id()));
+        // The default level is `Error`, which must not hide the `Warn` reports.
+        loge::Builder::new()
+            .format(loge::LogeFormat::Template("{message}".parse().unwrap()))
+            .file(&path)
+            .console(false)
+            .asynchronous(
+                loge::Queue::new(2)
+                    .overflow(loge::Overflow::DropNewest)
+                    .report_interval(Duration::from_millis(50)),
+            )
+            .init();
+
+        (0..2000).for_each(|i| error!("line {}", i));
+        let dropped = loge::dropped_records();
+        assert!(dropped > 0);
+
+        let reported = || {
+            let content = fs::read_to_string(&path).unwrap();
+            let (mut written, mut reported) = (0, 0);
+            for line in content.lines() {
+                match line.strip_suffix(" log records were dropped because the queue was full") {
+                    Some(count) => reported += count.parse::<u64>().unwrap(),
+                    None => written += 1,
+                }
+            }
+            (written, reported)
+        };
+        let start = Instant::now();
+        while reported().1 < dropped && start.elapsed() < Duration::from_secs(10) {
+            thread::sleep(Duration::from_millis(20));
+        }
+        let (written, reported) = reported();
+        assert_eq!(reported, dropped);
+        assert_eq!(written + reported, 2000);
+        assert_eq!(loge::dropped_records(), dropped);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,48 @@
+#[cfg(test)]
+mod tests {
+    extern crate loge;
+    use log::info;
+    use std::time::{Duration, Instant};
+    use std::{env, fs, thread};
+
+    #[test]
+    fn test_async_loge() {
+        let path = env::temp_dir().join(format!("loge-async-{}.log", std::process::id()));
+        loge::Builder::new()
+            .level(log::Level::Info)
+            .format(loge::LogeFormat::Template("{message}".parse().unwrap()))
+            .file(&path)
+            .asynchronous(
+                loge::Queue::new(8)
+                    .overflow(loge::Overflow::DropNewest)
+                    .report_interval(Duration::from_millis(50)),
+            )
+            .init();
+
+        let threads: Vec<_> = (0..4)
+            .map(|t| thread::spawn(move || (0..250).for_each(|i| info!("thread {} line {}", t, i))))
+            .collect();
+        threads.into_iter().for_each(|t| t.join().unwrap());
+
+        // Every record is either written or accounted for in a drop report.
+        let count = || {
+            let content = fs::read_to_string(&path).unwrap();
+            content
+                .lines()
+                .map(|line| {
+                    match line.strip_suffix(" log records were dropped because the queue was full")
+                    {
+                        Some(dropped) => dropped.parse().unwrap(),
+                        None => 1,
+                    }
+                })
+                .sum::<u64>()
+        };
+        let start = Instant::now();
+        while count() < 1000 && start.elapsed() < Duration::from_secs(10) {
+            thread::sleep(Duration::from_millis(20));
+        }
+        assert_eq!(count(), 1000);
+        fs::remove_file(&path).unwrap();
+    }
+}
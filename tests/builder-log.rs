@@ -0,0 +1,30 @@
+#[cfg(test)]
+mod tests {
+    extern crate loge;
+    use log::{debug, error, info, trace, warn, Level};
+    use std::{env, fs};
+
+    #[test]
+    fn test_builder_loge() {
+        let path = env::temp_dir().join(format!("loge-builder-{}.log", std::process::id()));
+        loge::Builder::new()
+            .level(Level::Debug)
+            .format(loge::LogeFormat::Fileline)
+            .file(&path)
+            .init();
+
+        trace!("one level deep!");
+        debug!("deboogging");
+        info!("such information");
+        warn!("o_O");
+        error!("boom");
+
+        let content = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(!content.contains("one level deep!"));
+        assert!(content.contains("deboogging"));
+        assert!(content.contains("boom"));
+        // The file never receives colors.
+        assert!(!content.contains('\u{1b}'));
+    }
+}
@@ -0,0 +1,58 @@
+#[cfg(test)]
+mod tests {
+    extern crate loge;
+    use log::info;
+    use std::process::Command;
+    use std::{env, fs};
+
+    // Logs from a child process, so that each run reads its own environment.
+    #[test]
+    fn child() {
+        let file = match env::var("LOGE_COLOR_CHILD") {
+            Ok(file) => file,
+            Err(_) => return,
+        };
+        let choice = match env::var("LOGE_TEST_COLOR").as_deref() {
+            Ok("always") => loge::ColorChoice::Always,
+            Ok("never") => loge::ColorChoice::Never,
+            _ => loge::ColorChoice::Auto,
+        };
+        loge::Builder::new()
+            .level(log::Level::Info)
+            .format(loge::LogeFormat::Template("<{level:level}>".parse().unwrap()))
+            .color(choice)
+            .file(file)
+            .init();
+        info!("record");
+    }
+
+    // Whether the piped stdout and the file of a child got colors.
+    fn run(choice: &str, vars: &[(&str, &str)]) -> (bool, bool) {
+        let file = env::temp_dir().join(format!("loge-color-{}.log", std::process::id()));
+        let mut command = Command::new(env::current_exe().unwrap());
+        command
+            .args(["tests::child", "--exact", "--nocapture", "--test-threads=1"])
+            .env("LOGE_COLOR_CHILD", &file)
+            .env("LOGE_TEST_COLOR", choice);
+        for name in ["NO_COLOR", "CLICOLOR", "CLICOLOR_FORCE", "TERM"] {
+            command.env_remove(name);
+        }
+        let output = command.envs(vars.iter().copied()).output().unwrap();
+        assert!(output.status.success());
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.contains("INFO"));
+        let written = fs::read_to_string(&file).unwrap();
+        fs::remove_file(&file).unwrap();
+        (stdout.contains("\x1b[32mINFO\x1b[0m"), written.contains('\x1b'))
+    }
+
+    #[test]
+    fn test_color_loge() {
+        assert_eq!(run("auto", &[]), (false, false));
+        assert_eq!(run("auto", &[("CLICOLOR_FORCE", "1")]), (true, false));
+        assert_eq!(run("auto", &[("CLICOLOR_FORCE", "1"), ("NO_COLOR", "1")]), (true, false));
+        assert_eq!(run("auto", &[("CLICOLOR_FORCE", "0")]), (false, false));
+        assert_eq!(run("always", &[("NO_COLOR", "1"), ("TERM", "dumb")]), (true, false));
+        assert_eq!(run("never", &[("CLICOLOR_FORCE", "1")]), (false, false));
+    }
+}
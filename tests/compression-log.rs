@@ -0,0 +1,47 @@
+#[cfg(all(test, feature = "gzip"))]
+mod tests {
+    extern crate loge;
+    use log::info;
+    use std::time::{Duration, Instant};
+    use std::{env, fs, thread};
+
+    #[test]
+    fn test_compression_loge() {
+        let dir = env::temp_dir().join(format!("loge-compression-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("app.log");
+        loge::Builder::new()
+            .level(log::Level::Info)
+            .format(loge::LogeFormat::Template("{message}".parse().unwrap()))
+            .file(&path)
+            .rotation(
+                loge::Rotation::new().max_bytes(200).keep(3).compress(loge::Compression::Gzip),
+            )
+            .init();
+
+        for i in 0..100 {
+            info!("compressible line {:03}", i);
+        }
+
+        // Compression happens in the background; wait for it to settle.
+        let names = |dir: &std::path::Path| {
+            let mut names: Vec<_> = fs::read_dir(dir)
+                .unwrap()
+                .map(|e| e.unwrap().file_name().into_string().unwrap())
+                .collect();
+            names.sort();
+            names
+        };
+        let expected = vec!["app.log", "app.log.1.gz", "app.log.2.gz", "app.log.3.gz"];
+        let start = Instant::now();
+        while names(&dir) != expected && start.elapsed() < Duration::from_secs(10) {
+            thread::sleep(Duration::from_millis(20));
+        }
+        assert_eq!(names(&dir), expected);
+        for name in &expected[1..] {
+            let content = fs::read(dir.join(name)).unwrap();
+            assert_eq!(&content[..2], &[0x1f, 0x8b], "{} is not gzip", name);
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
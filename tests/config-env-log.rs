@@ -0,0 +1,60 @@
+#[cfg(all(test, feature = "config"))]
+mod tests {
+    extern crate loge;
+    use log::{debug, info, warn, Level, LevelFilter};
+    use std::time::{Duration, Instant};
+    use std::{env, fs, thread};
+
+    fn wait_for<F: Fn() -> bool>(condition: F) {
+        let start = Instant::now();
+        while !condition() && start.elapsed() < Duration::from_secs(10) {
+            thread::sleep(Duration::from_millis(20));
+        }
+        assert!(condition());
+    }
+
+    #[test]
+    fn test_config_env_loge() {
+        let id = std::process::id();
+        let config = env::temp_dir().join(format!("loge-config-env-{}.toml", id));
+        let path = env::temp_dir().join(format!("loge-config-env-{}.log", id));
+        let settings = |level: &str| {
+            format!(
+                "level = {:?}\nformat = \"template:{{message}}\"\nconsole = false\nfile = {:?}\n",
+                level,
+                path.to_str().unwrap()
+            )
+        };
+        fs::write(&config, settings("error")).unwrap();
+        env::set_var("RUST_LOG", "config_env_log=debug");
+        loge::Builder::new()
+            .filter_module("builder", LevelFilter::Info)
+            .level_env("RUST_LOG")
+            .config_file(&config)
+            .watch_config(Duration::from_millis(20))
+            .init();
+        debug!("env before");
+        info!(target: "builder", "builder before");
+        warn!(target: "other", "hidden before");
+
+        // Raising the default level in the file keeps the directives of the
+        // environment and of the code.
+        fs::write(&config, settings("warn")).unwrap();
+        wait_for(|| log::log_enabled!(target: "other", Level::Warn));
+        assert_eq!(log::max_level(), LevelFilter::Debug);
+        debug!("env after");
+        info!(target: "builder", "builder after");
+        warn!(target: "other", "other after");
+        info!(target: "other", "hidden after");
+
+        log::logger().flush();
+        let content = fs::read_to_string(&path).unwrap();
+        let lines: Vec<_> = content.lines().collect();
+        assert_eq!(
+            lines,
+            ["env before", "builder before", "env after", "builder after", "other after"]
+        );
+        fs::remove_file(&config).unwrap();
+        fs::remove_file(&path).unwrap();
+    }
+}
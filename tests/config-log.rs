@@ -0,0 +1,64 @@
+#[cfg(all(test, feature = "config"))]
+mod tests {
+    extern crate loge;
+    use log::{error, info, warn, LevelFilter};
+    use std::time::{Duration, Instant};
+    use std::{env, fs, thread};
+
+    fn wait_for<F: Fn() -> bool>(condition: F) {
+        let start = Instant::now();
+        while !condition() && start.elapsed() < Duration::from_secs(10) {
+            thread::sleep(Duration::from_millis(20));
+        }
+        assert!(condition());
+    }
+
+    #[test]
+    fn test_config_loge() {
+        let id = std::process::id();
+        let config = env::temp_dir().join(format!("loge-config-{}.toml", id));
+        let path = env::temp_dir().join(format!("loge-config-{}.log", id));
+        let file = format!("[file]\npath = {:?}\n", path.to_str().unwrap());
+        fs::write(
+            &config,
+            format!(
+                "level = \"info\"\nformat = \"template:{{message}}\"\nconsole = false\n\
+                 stream = \"stderr\"\n{}",
+                file
+            ),
+        )
+        .unwrap();
+        loge::Builder::new().config_file(&config).watch_config(Duration::from_millis(20)).init();
+        info!("loaded");
+
+        // A valid change is applied.
+        fs::write(
+            &config,
+            format!(
+                "level = \"error\" # quieter\nformat = 'template:{{level}} {{message}}'\n{}",
+                file
+            ),
+        )
+        .unwrap();
+        wait_for(|| log::max_level() == LevelFilter::Error);
+        warn!("hidden");
+        error!("reloaded");
+
+        // An invalid change keeps the previous configuration, and is reported
+        // although warnings are filtered out.
+        fs::write(&config, format!("level = \"trace\"\nformat = \"xml\"\n{}", file)).unwrap();
+        let content = || fs::read_to_string(&path).unwrap();
+        wait_for(|| content().contains("invalid configuration"));
+        assert_eq!(log::max_level(), LevelFilter::Error);
+
+        let content = content();
+        let lines: Vec<_> = content.lines().collect();
+        assert_eq!(lines[..2], ["loaded", "ERROR reloaded"]);
+        assert!(lines[2].starts_with("WARN invalid configuration in "));
+        assert!(
+            lines[2].ends_with(": unknown log format `xml`; keeping the previous configuration")
+        );
+        fs::remove_file(&config).unwrap();
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,42 @@
+#[cfg(test)]
+mod tests {
+    extern crate loge;
+    use log::info;
+    use loge::context;
+    use std::{env, fs, thread};
+
+    #[test]
+    fn test_context_loge() {
+        let path = env::temp_dir().join(format!("loge-context-{}.log", std::process::id()));
+        loge::Builder::new()
+            .level(log::Level::Info)
+            .format(loge::LogeFormat::Template("{message}|{context}".parse().unwrap()))
+            .file(&path)
+            .init();
+
+        let request = context::push("request_id", 7);
+        context::with_context("tenant", "acme corp", || {
+            info!("inner");
+            let _shadow = context::push("request_id", 8);
+            info!("shadowed");
+        });
+        thread::spawn(|| info!("other thread")).join().unwrap();
+        info!("outer");
+        drop(request);
+        info!("empty");
+
+        let content = fs::read_to_string(&path).unwrap();
+        let lines: Vec<_> = content.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "inner|request_id=7 tenant=\"acme corp\"",
+                "shadowed|request_id=8 tenant=\"acme corp\"",
+                "other thread|",
+                "outer|request_id=7",
+                "empty|",
+            ]
+        );
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,80 @@
+#[cfg(all(test, feature = "json", feature = "kv"))]
+mod tests {
+    extern crate loge;
+    use log::{error, info};
+    use serde_json::Value;
+    use std::{env, fs};
+
+    #[test]
+    fn test_ecs_loge() {
+        let path = env::temp_dir().join(format!("loge-ecs-{}.log", std::process::id()));
+        env::set_var("LOGE_FORMAT_TEST", "ecs");
+        env::set_var("SERVICE_NAME", "checkout");
+        env::set_var("SERVICE_VERSION", "1.2.3");
+        loge::Builder::new()
+            .level(log::Level::Info)
+            .format_env("LOGE_FORMAT_TEST")
+            .file(&path)
+            .console(false)
+            .init();
+
+        let _guard = loge::context::push("trace.id", "7f3a");
+        info!("user.id" = 42, message = "ignored"; "payment accepted");
+        let line = line!() - 1;
+        error!(target: "db", "connection lost");
+
+        let lines = fs::read_to_string(&path).unwrap();
+        let lines: Vec<Value> = lines.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(lines.len(), 2);
+
+        let first = &lines[0];
+        // RFC 3339 in UTC, e.g. `2026-10-18T05:21:07.412Z`.
+        let timestamp = first["@timestamp"].as_str().unwrap();
+        assert_eq!(timestamp.len(), 24);
+        assert!(timestamp.ends_with('Z') && &timestamp[10..11] == "T", "{}", timestamp);
+        assert_eq!(first["log"]["level"], "info");
+        assert_eq!(first["log"]["logger"], "ecs_log::tests");
+        assert_eq!(first["log"]["origin"]["file"]["name"], "tests/ecs-log.rs");
+        assert_eq!(first["log"]["origin"]["file"]["line"], line);
+        assert_eq!(first["message"], "payment accepted");
+        assert_eq!(first["service"]["name"], "checkout");
+        assert_eq!(first["service"]["version"], "1.2.3");
+        assert_eq!(first["process"]["pid"], std::process::id());
+        assert_eq!(first["process"]["thread"]["name"], "tests::test_ecs_loge");
+        assert_eq!(first["ecs"]["version"], "8.11.0");
+        assert_eq!(first["trace.id"], "7f3a");
+        assert_eq!(first["user.id"], 42);
+
+        let second = &lines[1];
+        assert_eq!(second["log"]["level"], "error");
+        assert_eq!(second["log"]["logger"], "db");
+        assert_eq!(second["message"], "connection lost");
+        assert!(second.get("user.id").is_none());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_ecs_unknown_location_loge() {
+        use loge::Formatter;
+
+        let record = log::Record::builder()
+            .args(format_args!("from a bridge"))
+            .level(log::Level::Warn)
+            .target("bridge")
+            .build();
+        let cx = loge::FormatContext::new(std::time::SystemTime::now(), &[], None);
+        let mut buf = Vec::new();
+        loge::EcsFormatter.format(&record, &cx, &mut buf).unwrap();
+        let line: Value = serde_json::from_slice(&buf).unwrap();
+        // An unknown location is left out rather than given a placeholder.
+        assert!(line["log"].get("origin").is_none(), "{}", line);
+        assert_eq!(line["log"]["level"], "warn");
+
+        let record =
+            log::Record::builder().args(format_args!("no line")).file(Some("a.rs")).build();
+        buf.clear();
+        loge::EcsFormatter.format(&record, &cx, &mut buf).unwrap();
+        let line: Value = serde_json::from_slice(&buf).unwrap();
+        assert_eq!(line["log"]["origin"], serde_json::json!({ "file": { "name": "a.rs" } }));
+    }
+}
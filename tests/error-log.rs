@@ -0,0 +1,26 @@
+#[cfg(test)]
+mod tests {
+    extern crate loge;
+    use log::error;
+    use std::{env, fs};
+
+    #[test]
+    fn test_error_loge() {
+        let id = std::process::id();
+        // The directory does not exist, so every write to the file fails.
+        let path = env::temp_dir().join(format!("loge-missing-{}", id)).join("app.log");
+        let fallback = env::temp_dir().join(format!("loge-fallback-{}.log", id));
+        loge::Builder::new()
+            .format(loge::LogeFormat::Template("{message}".parse().unwrap()))
+            .file(&path)
+            .on_error(loge::OnError::Fallback(loge::Fallback::File(fallback.clone())))
+            .init();
+
+        error!("first");
+        error!("second");
+
+        assert_eq!(loge::failed_writes().file, 2);
+        assert_eq!(fs::read_to_string(&fallback).unwrap(), "first\nsecond\n");
+        fs::remove_file(&fallback).unwrap();
+    }
+}
@@ -0,0 +1,32 @@
+#[cfg(test)]
+mod tests {
+    extern crate loge;
+    use log::{debug, error, info, trace, warn};
+    use std::{env, fs};
+
+    #[test]
+    fn test_filter_loge() {
+        let path = env::temp_dir().join(format!("loge-filter-{}.log", std::process::id()));
+        env::set_var("LOGE_FILTER_TEST", "WARN,app=debug,app::db=trace,noisy=off/by");
+        loge::Builder::new().level_env("LOGE_FILTER_TEST").file(&path).init();
+        assert_eq!(log::max_level(), log::LevelFilter::Trace);
+
+        // Every message but the last one contains the pattern.
+        info!(target: "other", "other info dropped by the default level");
+        warn!(target: "other", "other warn kept by the default level");
+        debug!(target: "app", "app debug kept by its module level");
+        trace!(target: "app", "app trace dropped by its module level");
+        trace!(target: "app::db", "db trace kept by its submodule level");
+        trace!(target: "app_extra", "prefix without module boundary dropped by the default level");
+        error!(target: "noisy::inner", "noisy error dropped by its parent module level");
+        warn!(target: "app", "app warn dropped without the pattern");
+
+        let content = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let lines: Vec<_> = content.lines().collect();
+        assert_eq!(lines.len(), 3, "{}", content);
+        assert!(lines[0].contains("other warn kept"));
+        assert!(lines[1].contains("app debug kept"));
+        assert!(lines[2].contains("db trace kept"));
+    }
+}
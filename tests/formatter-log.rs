@@ -0,0 +1,88 @@
+#[cfg(test)]
+mod tests {
+    extern crate loge;
+    use log::{info, warn};
+    use std::io::{self, Write};
+    use std::time::UNIX_EPOCH;
+    use std::{env, fs};
+
+    struct Short;
+
+    impl loge::Formatter for Short {
+        fn format(
+            &self,
+            record: &log::Record,
+            cx: &loge::FormatContext,
+            buf: &mut Vec<u8>,
+        ) -> io::Result<()> {
+            // Every record starts from an empty buffer.
+            assert!(buf.is_empty());
+            let time = cx.time().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+            write!(
+                buf,
+                "{} {} {} color={}",
+                time,
+                record.level(),
+                record.args(),
+                cx.theme().is_some()
+            )?;
+            for (key, value) in cx.context() {
+                write!(buf, " {}={}", key, value)?;
+            }
+            Ok(())
+        }
+    }
+
+    // Wraps a built-in layout, dropping records about secrets.
+    struct Redacting(loge::TargetFormatter);
+
+    impl loge::Formatter for Redacting {
+        fn format(
+            &self,
+            record: &log::Record,
+            cx: &loge::FormatContext,
+            buf: &mut Vec<u8>,
+        ) -> io::Result<()> {
+            if record.args().to_string().contains("secret") {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "redacted"));
+            }
+            self.0.format(record, cx, buf)
+        }
+    }
+
+    #[test]
+    fn test_formatter_loge() {
+        let id = std::process::id();
+        let short = env::temp_dir().join(format!("loge-formatter-short-{}.log", id));
+        let target = env::temp_dir().join(format!("loge-formatter-target-{}.log", id));
+        let copy = env::temp_dir().join(format!("loge-formatter-copy-{}.log", id));
+        loge::Builder::new()
+            .level(log::Level::Info)
+            .format(loge::LogeFormat::Custom(Box::new(Short)))
+            .file(&short)
+            .console(false)
+            .on_error(loge::OnError::Ignore)
+            .output(
+                loge::Output::file(&target)
+                    .format(loge::LogeFormat::Custom(Box::new(Redacting(loge::TargetFormatter)))),
+            )
+            .output(loge::Output::file(&copy).format(loge::LogeFormat::Custom(Box::new(Short))))
+            .init();
+
+        info!("first");
+        loge::context::with_context("request_id", "7f3a", || warn!("the secret is 42"));
+
+        let lines = fs::read_to_string(&short).unwrap();
+        let lines: Vec<_> = lines.lines().map(|line| line.split_once(' ').unwrap()).collect();
+        assert_eq!(lines[0].1, "INFO first color=false");
+        assert_eq!(lines[1].1, "WARN the secret is 42 color=false request_id=7f3a");
+        // Every output gets the same time for a record.
+        assert_eq!(fs::read_to_string(&copy).unwrap(), fs::read_to_string(&short).unwrap());
+        let lines = fs::read_to_string(&target).unwrap();
+        assert_eq!(lines.lines().count(), 1);
+        assert!(lines.trim_end().ends_with("INFO ] formatter_log::tests ... first"));
+        fs::remove_file(&short).unwrap();
+        fs::remove_file(&target).unwrap();
+        fs::remove_file(&copy).unwrap();
+    }
+}
@@ -0,0 +1,145 @@
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    extern crate loge;
+    use log::{error, info};
+    use serde_json::Value;
+    use std::collections::BTreeMap;
+    use std::io::{self, Read};
+    use std::net::{TcpListener, UdpSocket};
+
+    fn sink(sink: loge::GelfSink) -> loge::Output {
+        loge::Output::sink(sink).format(loge::LogeFormat::Gelf)
+    }
+
+    // Receive one GELF message, putting its chunks back together.
+    fn recv(udp: &UdpSocket) -> Vec<u8> {
+        let mut buf = [0; 2048];
+        let mut chunks = BTreeMap::new();
+        loop {
+            let len = udp.recv(&mut buf).unwrap();
+            if buf[..2] != [0x1e, 0x0f] {
+                return buf[..len].to_vec();
+            }
+            let count = buf[11];
+            assert!(buf[10] < count && count <= 128);
+            chunks.insert(buf[10], buf[12..len].to_vec());
+            if chunks.len() == count as usize {
+                return chunks.into_values().flatten().collect();
+            }
+        }
+    }
+
+    #[test]
+    fn test_gelf_loge() {
+        std::env::set_var("SERVICE_NAME", "checkout");
+        let udp = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let tcp = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = udp.local_addr().unwrap().to_string();
+
+        loge::Builder::new()
+            .level(log::Level::Info)
+            .console(false)
+            .output(sink(loge::GelfSink::udp(address).chunk_size(64)))
+            .output(sink(loge::GelfSink::tcp(tcp.local_addr().unwrap().to_string())))
+            .init();
+
+        info!("payment accepted");
+        let line = line!() - 1;
+        error!("payment failed\ncard declined");
+        log::logger().flush();
+
+        let first: Value = serde_json::from_slice(&recv(&udp)).unwrap();
+        assert_eq!(first["version"], "1.1");
+        assert_eq!(first["short_message"], "payment accepted");
+        assert!(first.get("full_message").is_none());
+        assert_eq!(first["level"], 6);
+        assert_eq!(first["_file"], "tests/gelf-log.rs");
+        assert_eq!(first["_line"], line);
+        assert_eq!(first["_target"], "gelf_log::tests");
+        assert_eq!(first["_service_name"], "checkout");
+        assert!(first["timestamp"].as_f64().unwrap() > 1.5e9);
+
+        let second: Value = serde_json::from_slice(&recv(&udp)).unwrap();
+        assert_eq!(second["short_message"], "payment failed");
+        assert_eq!(second["full_message"], "payment failed\ncard declined");
+        assert_eq!(second["level"], 3);
+
+        let (mut stream, _) = tcp.accept().unwrap();
+        let mut framed = Vec::new();
+        let mut buf = [0; 1024];
+        while framed.iter().filter(|&&b| b == 0).count() < 2 {
+            let len = stream.read(&mut buf).unwrap();
+            framed.extend_from_slice(&buf[..len]);
+        }
+        let messages: Vec<Value> = framed
+            .split(|&b| b == 0)
+            .filter(|m| !m.is_empty())
+            .map(|m| serde_json::from_slice(m).unwrap())
+            .collect();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0]["short_message"], "payment accepted");
+        assert_eq!(messages[1]["level"], 3);
+    }
+
+    #[cfg(feature = "kv")]
+    #[test]
+    fn test_gelf_fields_loge() {
+        use loge::Formatter;
+
+        let mut buf = Vec::new();
+        let kvs = [("user_id", 42), ("id", 7)];
+        let record = log::Record::builder()
+            .args(format_args!("signed in"))
+            .level(log::Level::Warn)
+            .target("auth")
+            .key_values(&kvs)
+            .build();
+        let cx = loge::FormatContext::new(std::time::SystemTime::now(), &[], None);
+        loge::GelfFormatter.format(&record, &cx, &mut buf).unwrap();
+        let message: Value = serde_json::from_slice(&buf).unwrap();
+        assert_eq!(message["level"], 4);
+        assert_eq!(message["_target"], "auth");
+        assert_eq!(message["_user_id"], 42);
+        // `_id` is reserved by GELF.
+        assert_eq!(message["_id_"], 7);
+        assert!(message.get("_id").is_none());
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gelf_gzip_loge() {
+        use loge::Sink;
+
+        let udp = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let mut sink = loge::GelfSink::udp(udp.local_addr().unwrap().to_string()).gzip();
+        let message =
+            format!("{{\"version\":\"1.1\",\"short_message\":\"{}\"}}", "x".repeat(20_000));
+        sink.write(&message).unwrap();
+        sink.flush().unwrap();
+
+        let compressed = recv(&udp);
+        assert_eq!(compressed[..2], [0x1f, 0x8b]);
+        let mut decoder = flate2::read::GzDecoder::new(&compressed[..]);
+        let mut decompressed = String::new();
+        decoder.read_to_string(&mut decompressed).unwrap();
+        assert_eq!(decompressed, message);
+    }
+
+    #[test]
+    fn test_gelf_tcp_retry_loge() {
+        use loge::Sink;
+        let address = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let mut sink = loge::GelfSink::tcp(address.to_string());
+        // Nothing listens, so the first message fails to connect, and the next ones
+        // fail at once rather than connect again.
+        assert!(sink.write("{}").is_err());
+        let tcp = TcpListener::bind(address).unwrap();
+        tcp.set_nonblocking(true).unwrap();
+        assert_eq!(sink.write("{}").unwrap_err().kind(), io::ErrorKind::NotConnected);
+        assert_eq!(tcp.accept().unwrap_err().kind(), io::ErrorKind::WouldBlock);
+        // Reopening connects again on the next message.
+        sink.reopen().unwrap();
+        sink.write("{}").unwrap();
+        assert!(tcp.accept().is_ok());
+    }
+}
@@ -0,0 +1,35 @@
+#[cfg(test)]
+mod tests {
+    extern crate loge;
+    use log::info;
+    use std::time::Duration;
+    use std::{env, fs, thread};
+
+    #[test]
+    fn test_guard_loge() {
+        let path = env::temp_dir().join(format!("loge-guard-{}.log", std::process::id()));
+        let guard = loge::Builder::new()
+            .level(log::Level::Info)
+            .format(loge::LogeFormat::Template("{message}".parse().unwrap()))
+            .file(&path)
+            .asynchronous(loge::Queue::new(16))
+            .init_with_guard()
+            .timeout(Duration::from_secs(10));
+
+        let threads: Vec<_> = (0..4)
+            .map(|t| thread::spawn(move || (0..100).for_each(|i| info!("thread {} line {}", t, i))))
+            .collect();
+        threads.into_iter().for_each(|t| t.join().unwrap());
+
+        // Dropping the guard drains the queue before returning.
+        drop(guard);
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 400);
+
+        // Records logged after shutdown are written synchronously.
+        info!("after shutdown");
+        assert!(loge::shutdown(Duration::from_secs(1)));
+        let content = fs::read_to_string(&path).unwrap();
+        assert_eq!(content.lines().last(), Some("after shutdown"));
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,47 @@
+#[cfg(test)]
+mod tests {
+    extern crate loge;
+    use log::{debug, info, LevelFilter};
+    use std::{env, fs};
+
+    mod db {
+        pub fn query() {
+            log::debug!("query");
+        }
+    }
+
+    #[test]
+    fn test_handle_loge() {
+        let path = env::temp_dir().join(format!("loge-handle-{}.log", std::process::id()));
+        let handle = loge::Builder::new()
+            .level(log::Level::Info)
+            .format(loge::LogeFormat::Template("{message}".parse().unwrap()))
+            .file(&path)
+            .init();
+
+        info!("info");
+        db::query();
+        assert_eq!(log::max_level(), LevelFilter::Info);
+
+        handle.set_module_level("handle_log::tests::db", LevelFilter::Debug);
+        assert_eq!(log::max_level(), LevelFilter::Debug);
+        db::query();
+        debug!("still hidden");
+
+        handle.set_format(loge::LogeFormat::Template("{level} {message}".parse().unwrap()));
+        handle.set_filters("warn");
+        assert_eq!(log::max_level(), LevelFilter::Warn);
+        db::query();
+        log::warn!("warned");
+
+        handle.set_level(LevelFilter::Trace);
+        debug!("visible");
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            content.lines().collect::<Vec<_>>(),
+            ["info", "query", "WARN warned", "DEBUG visible"]
+        );
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,100 @@
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    extern crate loge;
+    use log::{info, warn};
+    use std::fs::File;
+    use std::io::{Read, Seek, SeekFrom};
+    use std::os::unix::io::{AsRawFd, FromRawFd};
+    use std::os::unix::net::UnixDatagram;
+    use std::{env, fs};
+
+    // Decode the fields of a journal entry.
+    fn parse(mut entry: &[u8]) -> Vec<(String, String)> {
+        let mut fields = Vec::new();
+        while !entry.is_empty() {
+            let end = entry.iter().position(|&b| b == b'\n').unwrap();
+            let line = &entry[..end];
+            let (name, value, rest) = match line.iter().position(|&b| b == b'=') {
+                Some(eq) => (&line[..eq], &line[eq + 1..], &entry[end + 1..]),
+                None => {
+                    let rest = &entry[end + 1..];
+                    let mut len = [0; 8];
+                    len.copy_from_slice(&rest[..8]);
+                    let len = u64::from_le_bytes(len) as usize;
+                    (line, &rest[8..8 + len], &rest[8 + len + 1..])
+                }
+            };
+            let text = |bytes: &[u8]| String::from_utf8(bytes.to_vec()).unwrap();
+            fields.push((text(name), text(value)));
+            entry = rest;
+        }
+        fields
+    }
+
+    fn field<'a>(fields: &'a [(String, String)], name: &str) -> &'a str {
+        &fields.iter().find(|(key, _)| key == name).unwrap().1
+    }
+
+    // Receive a descriptor passed with `SCM_RIGHTS`, and read the file behind it.
+    fn recv_memfd(socket: &UnixDatagram) -> Vec<u8> {
+        unsafe {
+            let mut control = [0u64; 4];
+            let mut message: libc::msghdr = std::mem::zeroed();
+            message.msg_control = control.as_mut_ptr() as *mut libc::c_void;
+            message.msg_controllen = std::mem::size_of_val(&control) as _;
+            assert!(libc::recvmsg(socket.as_raw_fd(), &mut message, 0) >= 0);
+            let header = libc::CMSG_FIRSTHDR(&message);
+            assert_eq!((*header).cmsg_type, libc::SCM_RIGHTS);
+            let fd = std::ptr::read_unaligned(libc::CMSG_DATA(header) as *const libc::c_int);
+            let mut file = File::from_raw_fd(fd);
+            let mut entry = Vec::new();
+            // The descriptor shares the offset left at the end by the sink.
+            file.seek(SeekFrom::Start(0)).unwrap();
+            file.read_to_end(&mut entry).unwrap();
+            entry
+        }
+    }
+
+    #[test]
+    fn test_journald_loge() {
+        let path = env::temp_dir().join(format!("loge-journald-{}.sock", std::process::id()));
+        let journal = UnixDatagram::bind(&path).unwrap();
+        loge::Builder::new()
+            .level(log::Level::Info)
+            .console(false)
+            .output(
+                loge::Output::sink(loge::JournaldSink::socket(&path).syslog_identifier("billing"))
+                    .format(loge::LogeFormat::Template("{message}".parse().unwrap())),
+            )
+            .init();
+
+        {
+            let _guard = loge::context::push("request-id", "7f3a");
+            warn!(target: "db", retries = 3, _hidden = "x"; "slow query\nselect 1");
+        }
+
+        let mut buf = vec![0; 65536];
+        let len = journal.recv(&mut buf).unwrap();
+        let fields = parse(&buf[..len]);
+        assert_eq!(field(&fields, "MESSAGE"), "slow query\nselect 1");
+        assert_eq!(field(&fields, "PRIORITY"), "4");
+        assert_eq!(field(&fields, "SYSLOG_IDENTIFIER"), "billing");
+        assert_eq!(field(&fields, "CODE_FILE"), "tests/journald-log.rs");
+        assert!(field(&fields, "CODE_LINE").parse::<u32>().is_ok());
+        assert_eq!(field(&fields, "TARGET"), "db");
+        assert_eq!(field(&fields, "REQUEST_ID"), "7f3a");
+        #[cfg(feature = "kv")]
+        {
+            assert_eq!(field(&fields, "RETRIES"), "3");
+            assert_eq!(field(&fields, "HIDDEN"), "x");
+        }
+
+        // Too large for a datagram, so passed in a memory file.
+        let large = "x".repeat(1 << 20);
+        info!("{}", large);
+        let fields = parse(&recv_memfd(&journal));
+        assert_eq!(field(&fields, "MESSAGE"), large);
+        assert_eq!(field(&fields, "PRIORITY"), "6");
+        fs::remove_file(&path).unwrap();
+    }
+}
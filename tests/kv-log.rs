@@ -0,0 +1,31 @@
+#[cfg(all(test, feature = "kv", feature = "json"))]
+mod tests {
+    extern crate loge;
+    use log::{info, warn};
+    use std::{env, fs};
+
+    #[test]
+    fn test_kv_loge() {
+        let path = env::temp_dir().join(format!("loge-kv-{}.log", std::process::id()));
+        loge::Builder::new()
+            .level(log::Level::Info)
+            .format(loge::LogeFormat::Json)
+            .file(&path)
+            .init();
+
+        info!(user_id = 42, ratio = 0.5, admin = false, name = "ann lee"; "login");
+        warn!("no fields");
+
+        let content = fs::read_to_string(&path).unwrap();
+        let mut lines = content.lines().map(|line| serde_json::from_str(line).unwrap());
+        let record: serde_json::Value = lines.next().unwrap();
+        assert_eq!(record["message"], "login");
+        assert_eq!(
+            record["fields"],
+            serde_json::json!({"user_id": 42, "ratio": 0.5, "admin": false, "name": "ann lee"})
+        );
+        let record: serde_json::Value = lines.next().unwrap();
+        assert!(record.get("fields").is_none());
+        fs::remove_file(&path).unwrap();
+    }
+}
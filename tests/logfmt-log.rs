@@ -0,0 +1,41 @@
+#[cfg(all(test, feature = "kv", feature = "chrono"))]
+mod tests {
+    extern crate loge;
+    use log::{error, info};
+    use std::{env, fs};
+
+    #[test]
+    fn test_logfmt_loge() {
+        let path = env::temp_dir().join(format!("loge-logfmt-{}.log", std::process::id()));
+        env::set_var("LOGE_FORMAT_TEST", "logfmt");
+        loge::Builder::new()
+            .level(log::Level::Info)
+            .format_env("LOGE_FORMAT_TEST")
+            .file(&path)
+            .console(false)
+            .init();
+
+        let _guard = loge::context::push("request id", "7f3a");
+        info!(user = "ann", quote = "say \"hi\"\n", empty = ""; "logged in");
+        let line = line!() - 1;
+        error!(target: "db", attempts = 3; "gave=up");
+
+        let lines = fs::read_to_string(&path).unwrap();
+        let lines: Vec<_> = lines.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let time = lines[0].split(' ').next().unwrap();
+        assert!(time.starts_with("time=") && time.contains('T'));
+        assert!(lines[0].ends_with(&format!(
+            " level=info target=logfmt_log::tests file=tests/logfmt-log.rs line={} \
+             msg=\"logged in\" request_id=7f3a user=ann quote=\"say \\\"hi\\\"\\n\" empty=\"\"",
+            line
+        )));
+        // The location is in `file` and `line`, not appended to error messages.
+        assert!(lines[1].ends_with(&format!(
+            " level=error target=db file=tests/logfmt-log.rs line={} msg=\"gave=up\" \
+             request_id=7f3a attempts=3",
+            line + 2
+        )));
+        fs::remove_file(&path).unwrap();
+    }
+}
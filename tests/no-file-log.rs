@@ -0,0 +1,51 @@
+#[cfg(test)]
+mod tests {
+    extern crate loge;
+    use log::info;
+    use std::io::{self, Write};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    static RENDERED: AtomicUsize = AtomicUsize::new(0);
+
+    struct Counting;
+
+    impl loge::Formatter for Counting {
+        fn format(
+            &self,
+            record: &log::Record,
+            _: &loge::FormatContext,
+            buf: &mut Vec<u8>,
+        ) -> io::Result<()> {
+            RENDERED.fetch_add(1, Ordering::SeqCst);
+            write!(buf, "{}", record.args())
+        }
+    }
+
+    struct Lines(Arc<Mutex<Vec<String>>>);
+
+    impl loge::Sink for Lines {
+        fn write(&mut self, line: &str) -> io::Result<()> {
+            self.0.lock().unwrap().push(line.to_owned());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_no_file_loge() {
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        loge::Builder::new()
+            .level(log::Level::Info)
+            .format(loge::LogeFormat::Custom(Box::new(Counting)))
+            .console(false)
+            .output(loge::Output::sink(Lines(lines.clone())).color(loge::ColorChoice::Always))
+            .init();
+
+        info!("first");
+        info!("second");
+
+        // Without a file, records are rendered for the colored output only.
+        assert_eq!(*lines.lock().unwrap(), ["first", "second"]);
+        assert_eq!(RENDERED.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,49 @@
+#[cfg(test)]
+mod tests {
+    extern crate loge;
+    use log::{debug, info, trace};
+    use std::{env, fs};
+
+    #[test]
+    fn test_output_loge() {
+        let id = std::process::id();
+        let main = env::temp_dir().join(format!("loge-output-main-{}.log", id));
+        let debug = env::temp_dir().join(format!("loge-output-debug-{}.log", id));
+        let handle = loge::Builder::new()
+            .level(log::Level::Info)
+            .format(loge::LogeFormat::Template("{message}".parse().unwrap()))
+            .file(&main)
+            .console(false)
+            .output(
+                loge::Output::file(&debug)
+                    .level(log::Level::Debug)
+                    .filter_module("output_log::tests::noisy", log::LevelFilter::Off)
+                    .format(loge::LogeFormat::Template("{level} {message}".parse().unwrap())),
+            )
+            .init();
+        assert_eq!(log::max_level(), log::LevelFilter::Debug);
+
+        info!("both");
+        debug!("debug only");
+        trace!("neither");
+        noisy::log();
+
+        // The handle changes the settings shared by outputs without their own.
+        handle.set_format(loge::LogeFormat::Template("main {message}".parse().unwrap()));
+        info!("changed");
+
+        assert_eq!(fs::read_to_string(&main).unwrap(), "both\nnoisy\nmain changed\n");
+        assert_eq!(
+            fs::read_to_string(&debug).unwrap(),
+            "INFO both\nDEBUG debug only\nINFO changed\n"
+        );
+        fs::remove_file(&main).unwrap();
+        fs::remove_file(&debug).unwrap();
+    }
+
+    mod noisy {
+        pub fn log() {
+            log::info!("noisy");
+        }
+    }
+}
@@ -0,0 +1,37 @@
+#[cfg(test)]
+mod tests {
+    extern crate loge;
+    use std::{env, fs, thread};
+
+    #[test]
+    fn test_panic_loge() {
+        let path = env::temp_dir().join(format!("loge-panic-{}.log", std::process::id()));
+        // Panics are logged even when filtered out, e.g. as raised by other crates.
+        loge::Builder::new()
+            .format(loge::LogeFormat::Target)
+            .filter_module("panic", log::LevelFilter::Off)
+            .file(&path)
+            .init();
+        loge::install_panic_hook();
+
+        let result = thread::Builder::new()
+            .name("worker".to_owned())
+            .spawn(|| panic!("boom {}", 42))
+            .unwrap()
+            .join();
+        assert!(result.is_err());
+
+        let content = fs::read_to_string(&path).unwrap();
+        let line = content.lines().next().unwrap();
+        assert!(
+            line.contains(
+                " [ERROR] panic ... thread 'worker' panicked at 'boom 42', tests/panic-log.rs:"
+            ),
+            "{}",
+            line
+        );
+        // The location is given once.
+        assert_eq!(line.matches("tests/panic-log.rs").count(), 1, "{}", line);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,51 @@
+#[cfg(test)]
+mod tests {
+    extern crate loge;
+    use log::error;
+    use std::sync::{mpsc, Arc, Mutex};
+    use std::time::Duration;
+    use std::{io, panic, thread};
+
+    // Panics on `boom`, and keeps the other lines.
+    struct Panicking(Arc<Mutex<Vec<String>>>);
+
+    impl loge::Sink for Panicking {
+        fn write(&mut self, line: &str) -> io::Result<()> {
+            if line == "boom" {
+                panic!("sink failed on {}", line);
+            }
+            self.0.lock().unwrap().push(line.to_owned());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_panic_sink_loge() {
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        loge::Builder::new()
+            .format(loge::LogeFormat::Template("{message}".parse().unwrap()))
+            .console(false)
+            .output(loge::Output::sink(Panicking(lines.clone())))
+            .init();
+        loge::install_panic_hook();
+
+        // The hook must not wait for the sink the panicking thread holds.
+        let (done, finished) = mpsc::channel();
+        thread::spawn(move || {
+            let result = panic::catch_unwind(|| error!("boom"));
+            done.send(result.is_err()).unwrap();
+        });
+        assert_eq!(finished.recv_timeout(Duration::from_secs(10)), Ok(true));
+
+        // The sink is still usable afterwards, and a panic outside it is logged.
+        let (done, finished) = mpsc::channel();
+        thread::spawn(move || {
+            let result = panic::catch_unwind(|| panic!("elsewhere"));
+            done.send(result.is_err()).unwrap();
+        });
+        assert_eq!(finished.recv_timeout(Duration::from_secs(10)), Ok(true));
+        let lines = lines.lock().unwrap();
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].starts_with("thread '<unnamed>' panicked at 'elsewhere'"), "{}", lines[0]);
+    }
+}
@@ -0,0 +1,34 @@
+#[cfg(all(test, unix))]
+mod tests {
+    extern crate loge;
+    use log::error;
+    use std::{env, fs};
+
+    #[test]
+    fn test_reopen_loge() {
+        let path = env::temp_dir().join(format!("loge-reopen-{}.log", std::process::id()));
+        let moved = path.with_extension("log.1");
+        loge::Builder::new()
+            .format(loge::LogeFormat::Template("{message}".parse().unwrap()))
+            .file(&path)
+            .init();
+        loge::reopen_on_sighup().unwrap();
+
+        error!("first");
+        fs::rename(&path, &moved).unwrap();
+        error!("second");
+        loge::reopen().unwrap();
+        error!("third");
+        assert_eq!(fs::read_to_string(&moved).unwrap(), "first\nsecond\n");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "third\n");
+
+        // SIGHUP reopens the file before the next record.
+        fs::rename(&path, &moved).unwrap();
+        unsafe { libc::raise(libc::SIGHUP) };
+        error!("fourth");
+        assert_eq!(fs::read_to_string(&moved).unwrap(), "third\n");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "fourth\n");
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(&moved).unwrap();
+    }
+}
@@ -0,0 +1,28 @@
+#[cfg(all(test, unix))]
+mod tests {
+    extern crate loge;
+    use log::error;
+    use std::time::Duration;
+    use std::{env, fs, thread};
+
+    #[test]
+    fn test_reopen_moved_loge() {
+        let path = env::temp_dir().join(format!("loge-moved-{}.log", std::process::id()));
+        let moved = path.with_extension("log.1");
+        loge::Builder::new()
+            .format(loge::LogeFormat::Template("{message}".parse().unwrap()))
+            .file(&path)
+            .reopen_when_moved()
+            .init();
+
+        error!("first");
+        fs::rename(&path, &moved).unwrap();
+        // The path is checked at most once per second.
+        thread::sleep(Duration::from_millis(1100));
+        error!("second");
+        assert_eq!(fs::read_to_string(&moved).unwrap(), "first\n");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second\n");
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(&moved).unwrap();
+    }
+}
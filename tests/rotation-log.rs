@@ -0,0 +1,39 @@
+#[cfg(test)]
+mod tests {
+    extern crate loge;
+    use log::info;
+    use std::{env, fs, thread};
+
+    #[test]
+    fn test_rotation_loge() {
+        let dir = env::temp_dir().join(format!("loge-rotation-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("app.log");
+        loge::Builder::new()
+            .level(log::Level::Info)
+            .format(loge::LogeFormat::Template("{message}".parse().unwrap()))
+            .file(&path)
+            .rotation(loge::Rotation::new().max_bytes(100).keep(2))
+            .init();
+
+        let threads: Vec<_> = (0..4)
+            .map(|t| {
+                thread::spawn(move || (0..25).for_each(|i| info!("thread {} line {:02}", t, i)))
+            })
+            .collect();
+        threads.into_iter().for_each(|t| t.join().unwrap());
+
+        let mut names: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["app.log", "app.log.1", "app.log.2"]);
+        for name in &names {
+            let content = fs::read_to_string(dir.join(name)).unwrap();
+            assert!(content.len() <= 100, "{} has {} bytes", name, content.len());
+            assert!(content.lines().all(|line| line.len() == 16), "{}", content);
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
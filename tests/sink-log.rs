@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod tests {
+    extern crate loge;
+    use log::{debug, info, warn};
+    use std::io;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct Captured {
+        lines: Vec<String>,
+        records: Vec<loge::RecordData>,
+    }
+
+    struct Capture(Arc<Mutex<Captured>>);
+
+    impl loge::Sink for Capture {
+        fn write(&mut self, line: &str) -> io::Result<()> {
+            self.0.lock().unwrap().lines.push(line.to_owned());
+            Ok(())
+        }
+
+        fn structured(&self) -> bool {
+            true
+        }
+
+        fn write_record(&mut self, line: &str, record: &loge::RecordData) -> io::Result<()> {
+            self.write(line)?;
+            self.0.lock().unwrap().records.push(record.clone());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_sink_loge() {
+        let captured = Arc::new(Mutex::new(Captured::default()));
+        loge::Builder::new()
+            .console(false)
+            .output(
+                loge::Output::sink(Capture(captured.clone()))
+                    .level(log::Level::Info)
+                    .format(loge::LogeFormat::Template("{level} {message}".parse().unwrap())),
+            )
+            .init();
+
+        {
+            let _guard = loge::context::push("request", 7);
+            info!("handled");
+        }
+        debug!("hidden");
+        warn!(target: "custom", "careful");
+
+        let captured = captured.lock().unwrap();
+        assert_eq!(captured.lines, ["INFO handled", "WARN careful"]);
+        let handled = &captured.records[0];
+        assert_eq!(handled.level(), log::Level::Info);
+        assert_eq!(handled.message(), "handled");
+        assert_eq!(handled.file(), Some("tests/sink-log.rs"));
+        assert_eq!(handled.context(), [("request".to_owned(), "7".to_owned())]);
+        let careful = &captured.records[1];
+        assert_eq!(careful.target(), "custom");
+        assert!(careful.context().is_empty());
+    }
+}
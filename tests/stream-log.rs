@@ -0,0 +1,58 @@
+#[cfg(test)]
+mod tests {
+    extern crate loge;
+    use log::{error, info, warn};
+    use std::env;
+    use std::process::Command;
+
+    // Logs from a child process, so that its streams can be captured.
+    #[test]
+    fn child() {
+        if env::var("LOGE_STREAM_CHILD").is_err() {
+            return;
+        }
+        loge::Builder::new()
+            .level(log::Level::Info)
+            .format(loge::LogeFormat::Template("<{level:level} {message}>".parse().unwrap()))
+            .stream(loge::Stream::Stderr)
+            .stream_env("LOGE_TARGET")
+            .init();
+        info!("info record");
+        warn!("warn record");
+        error!("error record");
+    }
+
+    fn run(target: &str) -> (String, String) {
+        let output = Command::new(env::current_exe().unwrap())
+            .args(["tests::child", "--exact", "--nocapture", "--test-threads=1"])
+            .env("LOGE_STREAM_CHILD", "1")
+            .env("LOGE_TARGET", target)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        (stdout, stderr)
+    }
+
+    #[test]
+    fn test_stream_loge() {
+        let (stdout, stderr) = run("split");
+        assert!(stdout.contains("<INFO info record>"));
+        assert!(!stdout.contains("<WARN") && !stdout.contains("<ERROR"));
+        assert!(stderr.contains("<WARN warn record>\n<ERROR error record"));
+        assert!(!stderr.contains("<INFO"));
+
+        let (stdout, stderr) = run("stdout");
+        assert!(stdout.contains("<INFO info record>\n<WARN warn record>"));
+        assert!(!stderr.contains("record"));
+
+        // Unknown values keep the stream set in code, and lines piped into another
+        // program are never colored.
+        let (stdout, stderr) = run("nowhere");
+        assert!(!stdout.contains("record"));
+        assert!(!stderr.contains('\x1b'));
+        assert!(stderr.contains("unknown console stream `nowhere`"));
+        assert!(stderr.contains("<INFO info record>\n<WARN warn record>"));
+    }
+}
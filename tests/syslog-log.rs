@@ -0,0 +1,96 @@
+#[cfg(test)]
+mod tests {
+    extern crate loge;
+    use log::{info, warn};
+    use std::io::{self, Read};
+    use std::net::{TcpListener, UdpSocket};
+    use std::{env, fs};
+
+    fn sink(sink: loge::SyslogSink) -> loge::Output {
+        let sink = sink.app_name("my app").hostname("web-1").procid("42");
+        loge::Output::sink(sink).format(loge::LogeFormat::Template("{message}".parse().unwrap()))
+    }
+
+    #[test]
+    fn test_syslog_loge() {
+        let udp = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let tcp = TcpListener::bind("127.0.0.1:0").unwrap();
+        #[cfg(unix)]
+        let path = env::temp_dir().join(format!("loge-syslog-{}.sock", std::process::id()));
+        #[cfg(unix)]
+        let unix = std::os::unix::net::UnixDatagram::bind(&path).unwrap();
+
+        let builder = loge::Builder::new()
+            .level(log::Level::Info)
+            .console(false)
+            .output(sink(loge::SyslogSink::udp(udp.local_addr().unwrap().to_string())))
+            .output(sink(
+                loge::SyslogSink::tcp(tcp.local_addr().unwrap().to_string())
+                    .facility(loge::Facility::Local3),
+            ));
+        #[cfg(unix)]
+        let builder = builder
+            .output(sink(loge::SyslogSink::unix(&path).protocol(loge::SyslogProtocol::Rfc3164)));
+        builder.init();
+
+        #[cfg(feature = "chrono")]
+        let before = chrono::Local::now().format("%b %e %H:%M:%S").to_string();
+        info!("started");
+        warn!("two\nlines");
+        log::logger().flush();
+        #[cfg(feature = "chrono")]
+        let after = chrono::Local::now().format("%b %e %H:%M:%S").to_string();
+
+        let mut buf = [0; 1024];
+        let len = udp.recv(&mut buf).unwrap();
+        let message = String::from_utf8_lossy(&buf[..len]).into_owned();
+        // User facility (1) and informational severity (6).
+        assert!(message.starts_with("<14>1 20"), "{}", message);
+        assert!(message.ends_with("Z web-1 myapp 42 - - started"), "{}", message);
+
+        let (mut stream, _) = tcp.accept().unwrap();
+        let mut framed = String::new();
+        while framed.matches(" - - ").count() < 2 {
+            let len = stream.read(&mut buf).unwrap();
+            framed.push_str(&String::from_utf8_lossy(&buf[..len]));
+        }
+        // Local3 (19) with informational then warning (4) severity, octet-counted.
+        let first = framed.split_once(' ').unwrap();
+        let len: usize = first.0.parse().unwrap();
+        let (message, rest) = first.1.split_at(len);
+        assert!(message.starts_with("<158>1 ") && message.ends_with(" - - started"));
+        assert!(rest.contains(" <156>1 ") && rest.ends_with(" - - two\nlines"), "{}", rest);
+
+        #[cfg(unix)]
+        {
+            let len = unix.recv(&mut buf).unwrap();
+            let message = String::from_utf8_lossy(&buf[..len]).into_owned();
+            assert!(message.starts_with("<14>"), "{}", message);
+            assert!(message.ends_with(" web-1 myapp[42]: started"), "{}", message);
+            // `Mmm dd hh:mm:ss`
+            assert_eq!(message[4..].split(' ').filter(|s| !s.is_empty()).nth(2).unwrap().len(), 8);
+            // in local time, as receivers read it
+            #[cfg(feature = "chrono")]
+            assert!(message[4..].starts_with(&before) || message[4..].starts_with(&after));
+            fs::remove_file(&path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_syslog_tcp_retry_loge() {
+        use loge::Sink;
+        let address = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let mut sink = loge::SyslogSink::tcp(address.to_string());
+        // Nothing listens, so the first record fails to connect, and the next ones
+        // fail at once rather than connect again.
+        assert!(sink.write("first").is_err());
+        let tcp = TcpListener::bind(address).unwrap();
+        tcp.set_nonblocking(true).unwrap();
+        assert_eq!(sink.write("second").unwrap_err().kind(), io::ErrorKind::NotConnected);
+        assert_eq!(tcp.accept().unwrap_err().kind(), io::ErrorKind::WouldBlock);
+        // Reopening connects again on the next record.
+        sink.reopen().unwrap();
+        sink.write("third").unwrap();
+        assert!(tcp.accept().is_ok());
+    }
+}
@@ -0,0 +1,37 @@
+#[cfg(test)]
+mod tests {
+    extern crate loge;
+    use log::{error, info, trace};
+    use std::{env, fs};
+
+    #[test]
+    fn test_template_loge() {
+        assert!("{unknown}".parse::<loge::Template>().is_err());
+        assert!("{level:<5,sparkly}".parse::<loge::Template>().is_err());
+        assert!("{level".parse::<loge::Template>().is_err());
+
+        let path = env::temp_dir().join(format!("loge-template-{}.log", std::process::id()));
+        env::set_var("RUST_LOG", "info");
+        env::set_var(
+            "LOGE_FORMAT",
+            "template:{{{pid}}} [{level:<5,level}] {target:>6,bold} {file} {message}",
+        );
+        loge::init_with_file(&path);
+
+        trace!(target: "app", "hidden");
+        info!(target: "app", "such information");
+        error!(target: "app", "boom");
+
+        let content = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let pid = std::process::id();
+        let lines: Vec<_> = content.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                format!("{{{}}} [INFO ]    app tests/template-log.rs such information", pid),
+                format!("{{{}}} [ERROR]    app tests/template-log.rs boom", pid),
+            ]
+        );
+    }
+}
@@ -0,0 +1,57 @@
+#[cfg(test)]
+mod tests {
+    extern crate loge;
+    use log::info;
+    use std::{env, fs};
+
+    #[test]
+    fn test_theme_loge() {
+        let id = std::process::id();
+        let fileline = env::temp_dir().join(format!("loge-theme-fileline-{}.log", id));
+        let json = env::temp_dir().join(format!("loge-theme-json-{}.log", id));
+        let template = env::temp_dir().join(format!("loge-theme-template-{}.log", id));
+        let plain = env::temp_dir().join(format!("loge-theme-plain-{}.log", id));
+        let theme = loge::Theme::default()
+            .level(log::Level::Info, loge::Style::new().fg(loge::Color::Fixed(34)).italic());
+
+        // The preset named in the environment replaces the theme set in code.
+        env::set_var("LOGE_THEME_TEST", "solarized");
+        loge::Builder::new()
+            .level(log::Level::Info)
+            .console(false)
+            .theme(theme)
+            .theme_env("LOGE_THEME_TEST")
+            .output(
+                loge::Output::file(&fileline)
+                    .format(loge::LogeFormat::Fileline)
+                    .color(loge::ColorChoice::Always),
+            )
+            .output(
+                loge::Output::file(&json)
+                    .format(loge::LogeFormat::Json)
+                    .color(loge::ColorChoice::Always),
+            )
+            .output(
+                loge::Output::file(&template)
+                    .format(loge::LogeFormat::Template("{level:level} {message}".parse().unwrap()))
+                    .color(loge::ColorChoice::Always),
+            )
+            .output(loge::Output::file(&plain).format(loge::LogeFormat::Fileline))
+            .init();
+
+        info!("themed");
+
+        let lines = fs::read_to_string(&fileline).unwrap();
+        assert!(lines.contains("[\x1b[38;2;133;153;0mINFO \x1b[0m]"));
+        assert!(lines.contains("\x1b[1;38;2;181;137;0mtheme_log::tests\x1b[0m"));
+        let lines = fs::read_to_string(&json).unwrap();
+        assert!(lines
+            .contains("\x1b[38;2;38;139;210m\"message\"\x1b[0m: \x1b[38;2;42;161;152m\"themed\""));
+        let lines = fs::read_to_string(&template).unwrap();
+        assert_eq!(lines, "\x1b[38;2;133;153;0mINFO\x1b[0m themed\n");
+        assert!(!fs::read_to_string(&plain).unwrap().contains('\x1b'));
+        for path in [fileline, json, template, plain] {
+            fs::remove_file(path).unwrap();
+        }
+    }
+}